@@ -0,0 +1,140 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_egui::EguiContexts;
+
+use crate::input::actions::{Action, ActionState, BOOKMARK_SLOTS};
+
+use super::{
+    projection::{snapped_yaw, CameraMode},
+    CameraMotion, MainCamera, ZRotator,
+};
+
+/// A camera position to jump back to.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct Bookmark {
+    /// Translation of the camera pivot.
+    pub translation: Vec3,
+    /// Rotation of the camera pivot around Z.
+    pub rotation: Quat,
+    /// Distance of the perspective camera to its pivot.
+    pub zoom: f32,
+}
+
+/// Bookmarks stored with [`Action::StoreBookmark`] and recalled with [`Action::RecallBookmark`].
+/// Saved with the level.
+#[derive(Resource, Debug, Default, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct CameraBookmarks {
+    pub slots: [Option<Bookmark>; BOOKMARK_SLOTS as usize],
+}
+
+/// An animated move of the camera to a bookmark.
+#[derive(Resource, Debug)]
+pub(super) struct CameraTransition {
+    from: Bookmark,
+    to: Bookmark,
+    elapsed: f32,
+}
+
+/// Duration of the move to a recalled bookmark in seconds.
+const TRANSITION_DURATION: f32 = 0.6;
+
+/// The transforms of the camera rig a bookmark is taken from.
+#[derive(SystemParam)]
+pub(super) struct CameraRig<'w, 's> {
+    motion: Res<'w, CameraMotion>,
+    z_rotator: Query<'w, 's, &'static Transform, (With<ZRotator>, Without<MainCamera>)>,
+    camera: Query<'w, 's, &'static Transform, With<MainCamera>>,
+}
+
+impl CameraRig<'_, '_> {
+    /// The bookmark of the current camera position.
+    fn current(&self, mode: &CameraMode) -> Bookmark {
+        let zoom = match *mode {
+            CameraMode::Perspective => self.motion.distance(self.camera.single()),
+            CameraMode::Isometric {
+                perspective_zoom, ..
+            } => perspective_zoom,
+        };
+        let z_rotator = self.z_rotator.single();
+        Bookmark {
+            translation: z_rotator.translation,
+            rotation: z_rotator.rotation,
+            zoom,
+        }
+    }
+}
+
+pub(super) fn store_and_recall_bookmarks(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    actions: Res<ActionState>,
+    mut bookmarks: ResMut<CameraBookmarks>,
+    mut mode: ResMut<CameraMode>,
+    rig: CameraRig,
+) {
+    // Typing digits into a text field should not move the camera.
+    if contexts.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+    let current = rig.current(&mode);
+    if let Some(slot) =
+        (0..BOOKMARK_SLOTS).find(|&slot| actions.just_pressed(Action::StoreBookmark(slot)))
+    {
+        bookmarks.slots[slot as usize] = Some(current);
+        info!("Stored camera bookmark {}", slot + 1);
+        return;
+    }
+    let Some(bookmark) = (0..BOOKMARK_SLOTS)
+        .filter(|&slot| actions.just_pressed(Action::RecallBookmark(slot)))
+        .find_map(|slot| bookmarks.slots[slot as usize])
+    else {
+        return;
+    };
+
+    if let CameraMode::Isometric {
+        yaw,
+        perspective_zoom,
+    } = &mut *mode
+    {
+        // The isometric camera turns to its snapped rotation by itself.
+        *yaw = snapped_yaw(bookmark.rotation);
+        *perspective_zoom = bookmark.zoom;
+    }
+    commands.insert_resource(CameraTransition {
+        from: current,
+        to: bookmark,
+        elapsed: 0.0,
+    });
+}
+
+pub(super) fn animate_transition(
+    mut commands: Commands,
+    time: Res<Time>,
+    mode: Res<CameraMode>,
+    mut transition: ResMut<CameraTransition>,
+    mut motion: ResMut<CameraMotion>,
+    mut z_rotator: Query<&mut Transform, (With<ZRotator>, Without<MainCamera>)>,
+    mut camera: Query<&mut Transform, With<MainCamera>>,
+) {
+    transition.elapsed += time.delta_seconds();
+    let t = (transition.elapsed / TRANSITION_DURATION).min(1.0);
+    // Ease in and out.
+    let s = t * t * (3.0 - 2.0 * t);
+    let CameraTransition { from, to, .. } = *transition;
+
+    let mut z_rotator = z_rotator.single_mut();
+    z_rotator.translation = from.translation.lerp(to.translation, s);
+    if *mode == CameraMode::Perspective {
+        z_rotator.rotation = from.rotation.slerp(to.rotation, s);
+        let zoom = from.zoom + (to.zoom - from.zoom) * s;
+        camera.single_mut().translation.z = zoom;
+        motion.zoom = Some(zoom);
+        motion.distance = Some(zoom);
+    }
+
+    motion.pan_velocity = Vec3::ZERO;
+
+    if t >= 1.0 {
+        commands.remove_resource::<CameraTransition>();
+    }
+}
@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+
+use crate::height_grid::HeightGrid;
+
+use super::{CameraMotion, MainCamera, XRotator, ZRotator};
+
+/// How fast the pivot of the camera catches up with the terrain height, higher is faster.
+const FOLLOW_SHARPNESS: f32 = 8.0;
+/// Minimum height of the camera above the terrain.
+const CLEARANCE: f32 = 1.0;
+/// Points along the boom from the pivot to the camera checked against the terrain.
+const SIGHT_SAMPLES: u32 = 32;
+
+/// Height of the terrain of all grids at `position` in world space, `None` if there is no
+/// terrain.
+fn terrain_height(
+    height_grid_q: &Query<(&HeightGrid, &GlobalTransform)>,
+    position: Vec2,
+) -> Option<f32> {
+    height_grid_q
+        .iter()
+        .filter_map(|(height_grid, transform)| {
+            let local = transform
+                .affine()
+                .inverse()
+                .transform_point3(position.extend(0.0));
+            let height = height_grid.sample_height(local.xy())?;
+            Some(transform.transform_point(local.xy().extend(height)).z)
+        })
+        .reduce(f32::max)
+}
+
+/// Moves the pivot of the camera smoothly to the height of the terrain below it. Above void
+/// cells and off the grid the pivot keeps its height.
+pub(super) fn follow_terrain_height(
+    time: Res<Time>,
+    mut z_rotator: Query<&mut Transform, With<ZRotator>>,
+    height_grid_q: Query<(&HeightGrid, &GlobalTransform)>,
+) {
+    let mut z_rotator = z_rotator.single_mut();
+    let Some(target) = terrain_height(&height_grid_q, z_rotator.translation.xy()) else {
+        return;
+    };
+
+    let factor = 1.0 - (-FOLLOW_SHARPNESS * time.delta_seconds()).exp();
+    z_rotator.translation.z += (target - z_rotator.translation.z) * factor;
+}
+
+/// Shortens the boom of the camera to the farthest sample along it from which the pivot is in
+/// sight and that stays [`CLEARANCE`] above the terrain. The pitch is left as it is.
+pub(super) fn avoid_terrain_clipping(
+    motion: Res<CameraMotion>,
+    z_rotator: Query<&Transform, (With<ZRotator>, Without<MainCamera>)>,
+    x_rotator: Query<&Transform, (With<XRotator>, Without<MainCamera>)>,
+    mut camera: Query<&mut Transform, With<MainCamera>>,
+    height_grid_q: Query<(&HeightGrid, &GlobalTransform)>,
+) {
+    let z_rotator = z_rotator.single();
+    let mut camera = camera.single_mut();
+    let zoom = motion.distance(&camera);
+
+    let boom = z_rotator.rotation * x_rotator.single().rotation * Vec3::Z * zoom;
+    let below_terrain = |sample: u32, clearance: f32| {
+        let point = z_rotator.translation + boom * sample as f32 / SIGHT_SAMPLES as f32;
+        terrain_height(&height_grid_q, point.xy())
+            .is_some_and(|height| point.z < height + clearance)
+    };
+
+    // Start one sample away from the pivot, which sits on the terrain.
+    let clear = (1..=SIGHT_SAMPLES)
+        .take_while(|&sample| !below_terrain(sample, 0.0))
+        .filter(|&sample| !below_terrain(sample, CLEARANCE))
+        .last()
+        .unwrap_or(1);
+    camera.translation.z = zoom * clear as f32 / SIGHT_SAMPLES as f32;
+}
@@ -1,15 +1,63 @@
+pub mod bookmarks;
+mod follow;
 mod mouse_scroll;
+mod projection;
+pub mod settings;
 
-use bevy::prelude::*;
+use bevy::{input::mouse::MouseMotion, prelude::*, window::PrimaryWindow};
+use bookmarks::{CameraBookmarks, CameraTransition};
 use mouse_scroll::{AccumulatedScrolls, MouseScrollPlugin};
+use projection::{in_perspective, CameraMode};
+use settings::CameraSettings;
+
+use crate::{
+    config::ConfigPlugin,
+    height_grid::HeightGrid,
+    input::{
+        actions::{Action, ActionState},
+        HitPoint, TerrainRaycast,
+    },
+};
 
 pub struct GameCameraPlugin;
 
 impl Plugin for GameCameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(MouseScrollPlugin)
-            .add_systems(Startup, setup)
-            .add_systems(Update, (move_camera_xy, zoom_camera, rotate_camera));
+        app.add_plugins((
+            MouseScrollPlugin,
+            ConfigPlugin::<CameraSettings>::new("config/camera.ron"),
+        ))
+        .register_type::<settings::PitchCurve>()
+        .init_resource::<CameraMode>()
+        .init_resource::<CameraMotion>()
+        .init_resource::<CameraBookmarks>()
+        .register_type::<CameraBookmarks>()
+        .add_systems(Startup, setup)
+        .add_systems(
+            Update,
+            (
+                sanitize_settings,
+                (
+                    projection::toggle_camera_mode,
+                    bookmarks::store_and_recall_bookmarks,
+                ),
+                (
+                    move_camera_xy,
+                    edge_pan_camera,
+                    drag_camera,
+                    zoom_to_cursor.run_if(in_perspective),
+                    zoom_camera.after(zoom_to_cursor).run_if(in_perspective),
+                    rotate_camera.run_if(in_perspective),
+                    projection::rotate_isometric_camera,
+                    projection::zoom_isometric_camera.run_if(not(in_perspective)),
+                ),
+                bookmarks::animate_transition.run_if(resource_exists::<CameraTransition>),
+                clamp_camera_to_grids,
+                follow::follow_terrain_height,
+                follow::avoid_terrain_clipping.run_if(in_perspective),
+            )
+                .chain(),
+        );
     }
 }
 
@@ -19,8 +67,31 @@ pub struct MainCamera;
 #[derive(Component)]
 struct ZRotator;
 
-#[derive(Component)]
-struct XRotator;
+/// The smoothed zoom and panning of the camera.
+#[derive(Resource, Debug, Default)]
+struct CameraMotion {
+    /// Distance to the pivot the perspective camera eases towards, taken from the camera when
+    /// `None`.
+    zoom: Option<f32>,
+    /// Velocity of the pivot from panning in world units per second.
+    pan_velocity: Vec3,
+    /// Distance of the perspective camera to its pivot before the boom is shortened to clear the
+    /// terrain, taken from the camera when `None`.
+    distance: Option<f32>,
+}
+
+impl CameraMotion {
+    /// Distance of the perspective camera to its pivot as zoomed, ignoring the terrain.
+    fn distance(&self, camera: &Transform) -> f32 {
+        self.distance.unwrap_or(camera.translation.z)
+    }
+}
+
+/// The pitch of the camera follows the zoom, `tilt` is added on top of it by dragging.
+#[derive(Component, Default)]
+struct XRotator {
+    tilt: f32,
+}
 
 fn setup(mut commands: Commands) {
     let camera = commands
@@ -41,7 +112,7 @@ fn setup(mut commands: Commands) {
                 )),
                 ..default()
             },
-            XRotator,
+            XRotator::default(),
             Name::new("X Rotator"),
         ))
         .add_child(camera)
@@ -51,83 +122,232 @@ fn setup(mut commands: Commands) {
         .add_child(x_rotator);
 }
 
+/// Keeps the ranges of the settings ordered however they were changed, by loading a config file
+/// or in the inspector, as the camera relies on them.
+fn sanitize_settings(mut settings: ResMut<CameraSettings>) {
+    if settings.is_changed() {
+        settings.bypass_change_detection().sanitize();
+    }
+}
+
+/// Pans the camera with the pan actions, easing in and out of the movement.
 fn move_camera_xy(
     time: Res<Time>,
-    mut x_rotator: Query<&mut Transform, With<ZRotator>>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    settings: Res<CameraSettings>,
+    actions: Res<ActionState>,
+    mut motion: ResMut<CameraMotion>,
+    mut z_rotator: Query<&mut Transform, With<ZRotator>>,
+    camera: Query<(&GlobalTransform, &Projection), With<MainCamera>>,
 ) {
-    let mut x_trans = x_rotator.single_mut();
+    let mut z_trans = z_rotator.single_mut();
 
-    let up = if keyboard_input.any_pressed([KeyCode::KeyW, KeyCode::ArrowUp]) {
-        1.0
-    } else {
-        0.0
+    let vertical = actions.value(Action::PanUp) - actions.value(Action::PanDown);
+    let horizontal = actions.value(Action::PanRight) - actions.value(Action::PanLeft);
+
+    let dir = vertical * z_trans.up() + horizontal * z_trans.right();
+    let speed = settings.pan_speed * visible_height(camera.single(), z_trans.translation);
+
+    let factor = settings.smoothing_factor(time.delta_seconds());
+    motion.pan_velocity = motion.pan_velocity.lerp(dir * speed, factor);
+    z_trans.translation += time.delta_seconds() * motion.pan_velocity;
+}
+
+/// Height of the area the camera shows around `pivot` in world units.
+fn visible_height((transform, projection): (&GlobalTransform, &Projection), pivot: Vec3) -> f32 {
+    match projection {
+        Projection::Perspective(perspective) => {
+            2.0 * transform.translation().distance(pivot) * (perspective.fov / 2.0).tan()
+        }
+        // The orthographic projection has a fixed vertical scale of one unit.
+        Projection::Orthographic(orthographic) => orthographic.scale,
+    }
+}
+
+/// Distance from the window border in pixels within which the cursor pans the camera.
+const EDGE_PAN_MARGIN: f32 = 10.0;
+/// Rotation in radians per pixel the mouse is dragged.
+const DRAG_ORBIT_SPEED: f32 = 0.005;
+
+/// Pans the camera while the cursor is at the border of the window.
+fn edge_pan_camera(
+    time: Res<Time>,
+    settings: Res<CameraSettings>,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+    mut z_rotator: Query<&mut Transform, With<ZRotator>>,
+    camera: Query<(&GlobalTransform, &Projection), With<MainCamera>>,
+) {
+    let Ok(window) = window_q.get_single() else {
+        return;
     };
-    let down = if keyboard_input.any_pressed([KeyCode::KeyS, KeyCode::ArrowDown]) {
-        -1.0
-    } else {
-        0.0
+    let Some(cursor) = window.cursor_position().filter(|_| window.focused) else {
+        return;
     };
+    let mut z_rotator = z_rotator.single_mut();
 
-    let left = if keyboard_input.any_pressed([KeyCode::KeyA, KeyCode::ArrowLeft]) {
+    let size = window.size();
+    let horizontal = if cursor.x < EDGE_PAN_MARGIN {
         -1.0
+    } else if cursor.x > size.x - EDGE_PAN_MARGIN {
+        1.0
     } else {
         0.0
     };
-    let right = if keyboard_input.any_pressed([KeyCode::KeyD, KeyCode::ArrowRight]) {
+    // The cursor position starts at the top of the window.
+    let vertical = if cursor.y < EDGE_PAN_MARGIN {
         1.0
+    } else if cursor.y > size.y - EDGE_PAN_MARGIN {
+        -1.0
     } else {
         0.0
     };
 
-    let dir = (up + down) * x_trans.up() + (left + right) * x_trans.right();
+    let dir = vertical * z_rotator.up() + horizontal * z_rotator.right();
+    let speed = settings.pan_speed * visible_height(camera.single(), z_rotator.translation);
+    z_rotator.translation += time.delta_seconds() * dir * speed;
+}
 
-    x_trans.translation += time.delta_seconds() * dir * 4.0f32;
+/// Orbits or pans the camera while the mouse is dragged with [`Action::DragOrbit`] or
+/// [`Action::DragPan`] held.
+fn drag_camera(
+    mode: Res<CameraMode>,
+    actions: Res<ActionState>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+    mut z_rotator: Query<&mut Transform, With<ZRotator>>,
+    mut x_rotator: Query<&mut XRotator>,
+    camera: Query<(&GlobalTransform, &Projection), With<MainCamera>>,
+) {
+    let delta: Vec2 = mouse_motion.read().map(|motion| motion.delta).sum();
+    if delta == Vec2::ZERO {
+        return;
+    }
+    let mut z_rotator = z_rotator.single_mut();
+
+    if actions.pressed(Action::DragOrbit) && *mode == CameraMode::Perspective {
+        z_rotator.rotate_z(-delta.x * DRAG_ORBIT_SPEED);
+        let mut x_rotator = x_rotator.single_mut();
+        x_rotator.tilt -= delta.y * DRAG_ORBIT_SPEED;
+    } else if actions.pressed(Action::DragPan) {
+        let Ok(window) = window_q.get_single() else {
+            return;
+        };
+        // Move the ground at the pivot along with the cursor.
+        let per_pixel = visible_height(camera.single(), z_rotator.translation) / window.height();
+        let dir = delta.y * z_rotator.up() - delta.x * z_rotator.right();
+        z_rotator.translation += dir * per_pixel;
+    }
 }
 
-const ZOOM_SPEED: f32 = 50.0;
-const MIN_ZOOM: f32 = 25.0;
-const MAX_ZOOM: f32 = 100.0;
-const MIN_X_ROT: f32 = 0.1745329; // 10 degree
-const MAX_X_ROT: f32 = 1.396263; // 80 degree
+/// Keeps the pivot of the camera above the area covered by the height grids.
+fn clamp_camera_to_grids(
+    mut z_rotator: Query<&mut Transform, With<ZRotator>>,
+    height_grid_q: Query<(&HeightGrid, &GlobalTransform)>,
+) {
+    let Some((min, max)) = height_grid_q
+        .iter()
+        .flat_map(|(height_grid, transform)| {
+            let size = height_grid.cells_count.as_vec2();
+            [
+                Vec2::ZERO,
+                size,
+                Vec2::new(size.x, 0.0),
+                Vec2::new(0.0, size.y),
+            ]
+            .map(|corner| transform.transform_point(corner.extend(0.0)).xy())
+        })
+        .fold(None, |bounds: Option<(Vec2, Vec2)>, point| {
+            Some(bounds.map_or((point, point), |(min, max)| {
+                (min.min(point), max.max(point))
+            }))
+        })
+    else {
+        return;
+    };
 
+    let mut z_rotator = z_rotator.single_mut();
+    let clamped = z_rotator.translation.xy().clamp(min, max);
+    z_rotator.translation.x = clamped.x;
+    z_rotator.translation.y = clamped.y;
+}
+
+/// Eases the perspective camera towards the zoom target and pitches it along the pitch curve.
 fn zoom_camera(
     time: Res<Time>,
+    settings: Res<CameraSettings>,
+    mut motion: ResMut<CameraMotion>,
     mut camera_zoom: Query<&mut Transform, (With<MainCamera>, Without<XRotator>)>,
-    mut x_rotator: Query<&mut Transform, (With<XRotator>, Without<MainCamera>)>,
-    accumulated_scrolls: Res<AccumulatedScrolls>,
+    mut x_rotator: Query<(&mut Transform, &mut XRotator), Without<MainCamera>>,
 ) {
     let mut camera_trans = camera_zoom.single_mut();
-    let mut x_rotator = x_rotator.single_mut();
+    let (mut x_rotator_trans, mut x_rotator) = x_rotator.single_mut();
 
-    let mut zoom = camera_trans.translation.z;
+    let zoom = eased_zoom(motion.distance(&camera_trans), &motion, &settings, &time);
 
-    zoom += time.delta_seconds() * ZOOM_SPEED * -1.0 * accumulated_scrolls.scroll;
+    camera_trans.translation.z = zoom;
+    motion.distance = Some(zoom);
+
+    let pitch = settings.pitch(zoom);
+    x_rotator.tilt = x_rotator
+        .tilt
+        .clamp(settings.min_pitch - pitch, settings.max_pitch - pitch);
+    x_rotator_trans.rotation = Quat::from_rotation_x(pitch + x_rotator.tilt);
+}
 
-    zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+/// Moves the zoom target by the scrolled distance and the camera towards the point under the
+/// cursor by as much as [`zoom_camera`] zooms in, so that the point stays roughly in place on
+/// screen.
+fn zoom_to_cursor(
+    time: Res<Time>,
+    settings: Res<CameraSettings>,
+    mut motion: ResMut<CameraMotion>,
+    camera_zoom: Query<&Transform, With<MainCamera>>,
+    mut z_rotator: Query<&mut Transform, (With<ZRotator>, Without<MainCamera>)>,
+    accumulated_scrolls: Res<AccumulatedScrolls>,
+    terrain_raycast: Res<TerrainRaycast>,
+) {
+    let previous_zoom = motion.distance(camera_zoom.single());
+    let target =
+        motion.zoom.unwrap_or(previous_zoom) - settings.zoom_speed * accumulated_scrolls.scroll;
+    motion.zoom = Some(target.clamp(settings.min_zoom, settings.max_zoom));
+    let zoom = eased_zoom(previous_zoom, &motion, &settings, &time);
 
-    let zoom_fraction = (zoom - MIN_ZOOM) / (MAX_ZOOM - MIN_ZOOM);
+    focus_cursor(
+        &mut z_rotator.single_mut(),
+        &terrain_raycast,
+        zoom / previous_zoom,
+    );
+}
 
-    camera_trans.translation.z = zoom;
+/// Moves the pivot towards the terrain under the cursor for a zoom that scales the view by
+/// `ratio`.
+fn focus_cursor(z_rotator: &mut Transform, terrain_raycast: &TerrainRaycast, ratio: f32) {
+    let Some(HitPoint { position, .. }) = terrain_raycast.hit_point else {
+        return;
+    };
+    let towards = (position - z_rotator.translation).xy() * (1.0 - ratio);
+    z_rotator.translation += towards.extend(0.0);
+}
 
-    x_rotator.rotation =
-        Quat::from_rotation_x(f32::interpolate(&MAX_X_ROT, &MIN_X_ROT, zoom_fraction));
+/// The zoom one frame closer to the zoom target.
+fn eased_zoom(zoom: f32, motion: &CameraMotion, settings: &CameraSettings, time: &Time) -> f32 {
+    let target = motion
+        .zoom
+        .unwrap_or(zoom)
+        .clamp(settings.min_zoom, settings.max_zoom);
+    zoom + (target - zoom) * settings.smoothing_factor(time.delta_seconds())
 }
 
 fn rotate_camera(
     time: Res<Time>,
     mut z_rotator: Query<&mut Transform, With<ZRotator>>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
 ) {
     let mut z_rotator = z_rotator.single_mut();
 
-    let rotate: f32 = if keyboard_input.pressed(KeyCode::KeyQ) {
-        -1.0
-    } else if keyboard_input.pressed(KeyCode::KeyE) {
-        1.0
-    } else {
+    let rotate = actions.value(Action::RotateRight) - actions.value(Action::RotateLeft);
+    if rotate == 0.0 {
         return;
-    };
+    }
 
     z_rotator.rotate_z(time.delta_seconds() * 90.0 * rotate.to_radians());
 }
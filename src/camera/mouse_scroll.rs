@@ -1,4 +1,7 @@
 use bevy::{input::mouse::MouseWheel, prelude::*};
+
+use super::settings::CameraSettings;
+
 pub(super) struct MouseScrollPlugin;
 
 #[derive(Resource, Default)]
@@ -15,13 +18,14 @@ impl Plugin for MouseScrollPlugin {
 fn accumulate_mouse_scroll(
     mut scrolls: EventReader<MouseWheel>,
     mut accumulated_scrolls: ResMut<AccumulatedScrolls>,
+    settings: Res<CameraSettings>,
 ) {
     use bevy::input::mouse::MouseScrollUnit;
 
     let scroll_sum: f32 = scrolls
         .read()
         .map(|scroll| match scroll.unit {
-            MouseScrollUnit::Line => 8.0 * scroll.y,
+            MouseScrollUnit::Line => settings.scroll_line_pixels * scroll.y,
             MouseScrollUnit::Pixel => scroll.y,
         })
         .sum();
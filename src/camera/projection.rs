@@ -0,0 +1,134 @@
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+use bevy::{prelude::*, render::camera::ScalingMode};
+
+use crate::input::{
+    actions::{Action, ActionState},
+    TerrainRaycast,
+};
+
+use super::{
+    focus_cursor, mouse_scroll::AccumulatedScrolls, CameraMotion, MainCamera, XRotator, ZRotator,
+};
+
+/// How the main camera looks at the world. Toggled with [`Action::ToggleProjection`].
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub enum CameraMode {
+    /// The perspective rig, pitched by the zoom.
+    #[default]
+    Perspective,
+    /// An orthographic projection at isometric angles. The camera turns towards `yaw`, which
+    /// the rotate actions change in steps of 90°. `perspective_zoom` is restored when switching back.
+    Isometric { yaw: f32, perspective_zoom: f32 },
+}
+
+/// Angle between the camera and the Z axis that makes the X and Y axes of the grid look the same
+/// length, i.e. the camera looks down at 35.26°.
+const ISO_PITCH: f32 = 0.9553166;
+/// Distance of the orthographic camera to its pivot, far enough to not cut into the terrain.
+const ISO_DISTANCE: f32 = 100.0;
+/// Height of the visible area in world units.
+const ISO_SCALE: f32 = 20.0;
+const MIN_ISO_SCALE: f32 = 5.0;
+const MAX_ISO_SCALE: f32 = 60.0;
+/// Change of the scale per scrolled pixel.
+const ISO_ZOOM_SPEED: f32 = 0.3;
+/// How fast the camera turns to a new snapped rotation, higher is faster.
+const SNAP_SHARPNESS: f32 = 12.0;
+
+pub(super) fn toggle_camera_mode(
+    actions: Res<ActionState>,
+    mut mode: ResMut<CameraMode>,
+    mut motion: ResMut<CameraMotion>,
+    mut camera: Query<(&mut Transform, &mut Projection), With<MainCamera>>,
+    z_rotator: Query<&Transform, (With<ZRotator>, Without<MainCamera>)>,
+) {
+    if !actions.just_pressed(Action::ToggleProjection) {
+        return;
+    }
+    let (mut camera_trans, mut projection) = camera.single_mut();
+    let distance = motion.distance(&camera_trans);
+    // The perspective zoom continues from where the camera is when switching back.
+    motion.zoom = None;
+    motion.distance = None;
+
+    match *mode {
+        CameraMode::Perspective => {
+            *mode = CameraMode::Isometric {
+                yaw: snapped_yaw(z_rotator.single().rotation),
+                perspective_zoom: distance,
+            };
+            camera_trans.translation.z = ISO_DISTANCE;
+            *projection = Projection::Orthographic(OrthographicProjection {
+                scaling_mode: ScalingMode::FixedVertical(1.0),
+                scale: ISO_SCALE,
+                ..default()
+            });
+        }
+        CameraMode::Isometric {
+            perspective_zoom, ..
+        } => {
+            *mode = CameraMode::Perspective;
+            camera_trans.translation.z = perspective_zoom;
+            *projection = Projection::Perspective(default());
+        }
+    }
+}
+
+/// The closest of the four diagonal views to the rotation around Z of `rotation`.
+pub(super) fn snapped_yaw(rotation: Quat) -> f32 {
+    let (yaw, ..) = rotation.to_euler(EulerRot::ZYX);
+    ((yaw - FRAC_PI_4) / FRAC_PI_2).round() * FRAC_PI_2 + FRAC_PI_4
+}
+
+/// Holds the camera at the isometric pitch and turns it to the snapped rotation.
+pub(super) fn rotate_isometric_camera(
+    time: Res<Time>,
+    actions: Res<ActionState>,
+    mut mode: ResMut<CameraMode>,
+    mut z_rotator: Query<&mut Transform, (With<ZRotator>, Without<XRotator>)>,
+    mut x_rotator: Query<&mut Transform, (With<XRotator>, Without<ZRotator>)>,
+) {
+    let CameraMode::Isometric { yaw, .. } = &mut *mode else {
+        return;
+    };
+    if actions.just_pressed(Action::RotateLeft) {
+        *yaw -= FRAC_PI_2;
+    } else if actions.just_pressed(Action::RotateRight) {
+        *yaw += FRAC_PI_2;
+    }
+
+    let mut z_rotator = z_rotator.single_mut();
+    let factor = 1.0 - (-SNAP_SHARPNESS * time.delta_seconds()).exp();
+    z_rotator.rotation = z_rotator
+        .rotation
+        .slerp(Quat::from_rotation_z(*yaw), factor);
+
+    x_rotator.single_mut().rotation = Quat::from_rotation_x(ISO_PITCH);
+}
+
+/// Zooms the orthographic projection towards the point under the cursor.
+pub(super) fn zoom_isometric_camera(
+    accumulated_scrolls: Res<AccumulatedScrolls>,
+    terrain_raycast: Res<TerrainRaycast>,
+    mut camera: Query<&mut Projection, With<MainCamera>>,
+    mut z_rotator: Query<&mut Transform, With<ZRotator>>,
+) {
+    let Projection::Orthographic(projection) = &mut *camera.single_mut() else {
+        return;
+    };
+
+    let previous_scale = projection.scale;
+    projection.scale = (previous_scale - ISO_ZOOM_SPEED * accumulated_scrolls.scroll)
+        .clamp(MIN_ISO_SCALE, MAX_ISO_SCALE);
+
+    focus_cursor(
+        &mut z_rotator.single_mut(),
+        &terrain_raycast,
+        projection.scale / previous_scale,
+    );
+}
+
+pub(super) fn in_perspective(mode: Res<CameraMode>) -> bool {
+    *mode == CameraMode::Perspective
+}
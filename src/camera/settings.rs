@@ -0,0 +1,149 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+
+/// How the pitch of the camera follows the zoom, from the lowest pitch at the closest zoom to the
+/// steepest at the farthest.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub enum PitchCurve {
+    /// The pitch changes evenly with the zoom.
+    Linear,
+    /// The pitch changes slowly at both ends of the zoom range.
+    Smooth,
+    /// Above 1 the camera stays low for longer while zooming out, below 1 it looks down sooner.
+    Power(f32),
+}
+
+impl PitchCurve {
+    /// Maps the fraction of the zoom range to the fraction of the pitch range.
+    pub fn sample(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            PitchCurve::Linear => t,
+            PitchCurve::Smooth => t * t * (3.0 - 2.0 * t),
+            PitchCurve::Power(exponent) => t.powf(exponent.max(0.01)),
+        }
+    }
+}
+
+/// Closest zoom distance the settings allow.
+pub const MIN_ZOOM: f32 = 1.0;
+/// Farthest zoom distance the settings allow.
+pub const MAX_ZOOM: f32 = 1000.0;
+
+/// Speeds and limits of the perspective camera. Saved to its own config file.
+#[derive(Resource, Debug, Clone, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct CameraSettings {
+    /// Change of the zoom distance per scrolled pixel.
+    pub zoom_speed: f32,
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    /// Pitch in radians at the farthest zoom, 0 looks straight down.
+    pub min_pitch: f32,
+    /// Pitch in radians at the closest zoom.
+    pub max_pitch: f32,
+    pub pitch_curve: PitchCurve,
+    /// Pixels a scrolled line of a mouse wheel counts as.
+    pub scroll_line_pixels: f32,
+    /// Speed of keyboard and edge panning in screen heights per second, so that the ground moves
+    /// across the screen equally fast at every zoom.
+    pub pan_speed: f32,
+    /// How fast zoom and panning catch up with the input, higher is snappier.
+    pub smoothing: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            zoom_speed: 0.8,
+            min_zoom: 25.0,
+            max_zoom: 100.0,
+            min_pitch: 0.1745329, // 10 degree
+            max_pitch: 1.396263,  // 80 degree
+            pitch_curve: PitchCurve::Linear,
+            scroll_line_pixels: 8.0,
+            pan_speed: 0.2,
+            smoothing: 10.0,
+        }
+    }
+}
+
+impl CameraSettings {
+    /// Orders the zoom and pitch ranges and keeps them within [`MIN_ZOOM`], [`MAX_ZOOM`] and
+    /// looking straight down to looking at the horizon, e.g. after loading a hand-edited config
+    /// file.
+    pub fn sanitize(&mut self) {
+        // Not `clamp`, which keeps NaN.
+        self.max_zoom = MAX_ZOOM.min(self.max_zoom).max(MIN_ZOOM);
+        self.min_zoom = self.min_zoom.max(MIN_ZOOM).min(self.max_zoom);
+        self.max_pitch = FRAC_PI_2.min(self.max_pitch).max(0.0);
+        self.min_pitch = self.min_pitch.max(0.0).min(self.max_pitch);
+    }
+
+    /// The pitch of a camera `zoom` away from its pivot.
+    pub fn pitch(&self, zoom: f32) -> f32 {
+        let fraction = (zoom - self.min_zoom) / (self.max_zoom - self.min_zoom).max(f32::EPSILON);
+        f32::interpolate(
+            &self.max_pitch,
+            &self.min_pitch,
+            self.pitch_curve.sample(fraction),
+        )
+    }
+
+    /// The part of the remaining distance smoothed values cover in a frame that took `delta`
+    /// seconds.
+    pub fn smoothing_factor(&self, delta: f32) -> f32 {
+        1.0 - (-self.smoothing * delta).exp()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pitch_follows_the_curve() {
+        let mut settings = CameraSettings::default();
+        let middle = (settings.min_zoom + settings.max_zoom) / 2.0;
+        let halfway = (settings.min_pitch + settings.max_pitch) / 2.0;
+
+        assert_eq!(settings.pitch(settings.min_zoom), settings.max_pitch);
+        assert_eq!(settings.pitch(settings.max_zoom), settings.min_pitch);
+        assert!((settings.pitch(middle) - halfway).abs() < 1e-5);
+
+        settings.pitch_curve = PitchCurve::Power(2.0);
+        assert!(settings.pitch(middle) > halfway);
+        assert_eq!(settings.pitch(settings.max_zoom * 2.0), settings.min_pitch);
+    }
+
+    #[test]
+    fn sanitize_orders_the_ranges() {
+        let mut settings = CameraSettings {
+            min_zoom: 50.0,
+            max_zoom: 10.0,
+            min_pitch: 1.0,
+            max_pitch: -1.0,
+            ..default()
+        };
+        settings.sanitize();
+
+        assert_eq!((settings.min_zoom, settings.max_zoom), (10.0, 10.0));
+        assert_eq!((settings.min_pitch, settings.max_pitch), (0.0, 0.0));
+
+        settings.max_zoom = f32::NAN;
+        settings.max_pitch = 4.0;
+        settings.sanitize();
+        assert_eq!(settings.max_zoom, MAX_ZOOM);
+        assert_eq!(settings.max_pitch, FRAC_PI_2);
+    }
+
+    #[test]
+    fn smoothing_is_frame_rate_independent() {
+        let settings = CameraSettings::default();
+        let once = settings.smoothing_factor(0.1);
+        let half = settings.smoothing_factor(0.05);
+
+        assert!((1.0 - (1.0 - half) * (1.0 - half) - once).abs() < 1e-6);
+    }
+}
@@ -1,13 +1,15 @@
 use bevy::prelude::*;
 use bevy::window::{ClosingWindow, Window};
 
+use crate::input::actions::{Action, ActionState};
+
 pub fn close_on_esc(
     mut commands: Commands,
     window: Query<(Entity, &Window)>,
-    keys: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
 ) {
     let (entity, _) = window.single();
-    if keys.just_pressed(KeyCode::Escape) {
+    if actions.just_pressed(Action::Quit) {
         commands.entity(entity).insert(ClosingWindow);
     }
 }
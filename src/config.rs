@@ -0,0 +1,153 @@
+use std::{any::TypeId, error::Error, fs, marker::PhantomData, path::Path};
+
+use bevy::{
+    prelude::*,
+    reflect::{
+        serde::{TypedReflectDeserializer, TypedReflectSerializer},
+        GetTypeRegistration, TypeRegistry,
+    },
+    scene::ron,
+};
+
+/// Saves the resource `T` to its config file with [`SaveConfig`] and loads it with
+/// [`LoadConfig`]. The file is also loaded at startup if it exists.
+pub struct ConfigPlugin<T> {
+    path: &'static str,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> ConfigPlugin<T> {
+    pub fn new(path: &'static str) -> Self {
+        Self {
+            path,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Plugin for ConfigPlugin<T>
+where
+    T: Resource + Default + FromReflect + GetTypeRegistration,
+{
+    fn build(&self, app: &mut App) {
+        app.init_resource::<T>()
+            .register_type::<T>()
+            .insert_resource(ConfigFile::<T> {
+                path: self.path,
+                marker: PhantomData,
+            })
+            .add_event::<SaveConfig<T>>()
+            .add_event::<LoadConfig<T>>()
+            .add_systems(Startup, load_config_on_startup::<T>)
+            .add_systems(Update, (save_config::<T>, load_config::<T>));
+    }
+}
+
+#[derive(Resource)]
+struct ConfigFile<T> {
+    path: &'static str,
+    marker: PhantomData<fn() -> T>,
+}
+
+/// Saves the resource `T` to its config file.
+#[derive(Event)]
+pub struct SaveConfig<T>(PhantomData<fn() -> T>);
+
+impl<T> Default for SaveConfig<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+/// Replaces the resource `T` with the one in its config file.
+#[derive(Event)]
+pub struct LoadConfig<T>(PhantomData<fn() -> T>);
+
+impl<T> Default for LoadConfig<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+pub type ConfigResult<T> = Result<T, Box<dyn Error>>;
+
+pub fn serialize_config<T: Reflect>(config: &T, registry: &TypeRegistry) -> ConfigResult<String> {
+    let serializer = TypedReflectSerializer::new(config, registry);
+    Ok(ron::ser::to_string_pretty(
+        &serializer,
+        ron::ser::PrettyConfig::default(),
+    )?)
+}
+
+pub fn deserialize_config<T: FromReflect>(
+    config: &str,
+    registry: &TypeRegistry,
+) -> ConfigResult<T> {
+    let type_name = std::any::type_name::<T>();
+    let registration = registry
+        .get(TypeId::of::<T>())
+        .ok_or_else(|| format!("{type_name} is not registered"))?;
+    let reflected = ron::Options::default().from_str_seed(
+        config,
+        TypedReflectDeserializer::new(registration, registry),
+    )?;
+    T::from_reflect(reflected.as_ref())
+        .ok_or_else(|| format!("the file does not contain a {type_name}").into())
+}
+
+fn read_config<T: FromReflect>(path: &str, registry: &TypeRegistry) -> ConfigResult<T> {
+    deserialize_config(&fs::read_to_string(path)?, registry)
+}
+
+fn load_config_on_startup<T: Resource + FromReflect>(
+    file: Res<ConfigFile<T>>,
+    registry: Res<AppTypeRegistry>,
+    mut config: ResMut<T>,
+) {
+    if !Path::new(file.path).exists() {
+        return;
+    }
+    match read_config(file.path, &registry.read()) {
+        Ok(loaded) => *config = loaded,
+        Err(err) => error!("Could not load {}: {err}", file.path),
+    }
+}
+
+fn save_config<T: Resource + Reflect>(
+    mut requests: EventReader<SaveConfig<T>>,
+    file: Res<ConfigFile<T>>,
+    registry: Res<AppTypeRegistry>,
+    config: Res<T>,
+) {
+    if requests.read().count() == 0 {
+        return;
+    }
+    let saved = serialize_config(&*config, &registry.read()).and_then(|serialized| {
+        if let Some(parent) = Path::new(file.path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+        Ok(fs::write(file.path, serialized)?)
+    });
+    match saved {
+        Ok(()) => info!("Saved {}", file.path),
+        Err(err) => error!("Could not save {}: {err}", file.path),
+    }
+}
+
+fn load_config<T: Resource + FromReflect>(
+    mut requests: EventReader<LoadConfig<T>>,
+    file: Res<ConfigFile<T>>,
+    registry: Res<AppTypeRegistry>,
+    mut config: ResMut<T>,
+) {
+    if requests.read().count() == 0 {
+        return;
+    }
+    match read_config(file.path, &registry.read()) {
+        Ok(loaded) => {
+            *config = loaded;
+            info!("Loaded {}", file.path);
+        }
+        Err(err) => error!("Could not load {}: {err}", file.path),
+    }
+}
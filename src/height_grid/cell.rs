@@ -1,13 +1,67 @@
-use super::corner::Corner;
+use bevy::reflect::Reflect;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+use super::{corner::Corner, direction::Direction};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Reflect)]
 pub struct Cell {
     heights: (u32, u32, u32, u32),
+    /// Void cells are holes in the terrain. They keep their heights so they can be filled again.
+    void: bool,
+    /// Overrides the diagonal the mesh builder would split the cell along.
+    diagonal: Option<Diagonal>,
+    feature: Option<CellFeature>,
+}
+
+/// Connects a cell to its neighbour in a direction. The edge towards the neighbour takes on the
+/// neighbour's heights so the cell climbs up or down to it instead of forming a cliff.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Reflect)]
+pub enum CellFeature {
+    Ramp(Direction),
+    Stairs(Direction),
+}
+
+impl CellFeature {
+    pub fn direction(&self) -> Direction {
+        match self {
+            CellFeature::Ramp(direction) | CellFeature::Stairs(direction) => *direction,
+        }
+    }
+}
+
+/// The diagonal a cell is split into two triangles along.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Reflect)]
+pub enum Diagonal {
+    /// From the bottom left to the top right corner.
+    Slash,
+    /// From the top left to the bottom right corner.
+    Backslash,
+}
+
+/// How a cell is split into triangles.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CellMeshType {
+    Shared,
+    Slash,
+    Backslash,
+}
+
+impl From<Diagonal> for CellMeshType {
+    fn from(diagonal: Diagonal) -> Self {
+        match diagonal {
+            Diagonal::Slash => CellMeshType::Slash,
+            Diagonal::Backslash => CellMeshType::Backslash,
+        }
+    }
 }
 
 impl From<(u32, u32, u32, u32)> for Cell {
     fn from(value: (u32, u32, u32, u32)) -> Self {
-        Self { heights: value }
+        Self {
+            heights: value,
+            void: false,
+            diagonal: None,
+            feature: None,
+        }
     }
 }
 
@@ -30,4 +84,23 @@ impl Cell {
             Corner::BottomRight => self.heights.3,
         }
     }
+
+    pub fn is_void(&self) -> bool {
+        self.void
+    }
+    pub fn set_void(&mut self, void: bool) {
+        self.void = void;
+    }
+    pub fn diagonal(&self) -> Option<Diagonal> {
+        self.diagonal
+    }
+    pub fn set_diagonal(&mut self, diagonal: Option<Diagonal>) {
+        self.diagonal = diagonal;
+    }
+    pub fn feature(&self) -> Option<CellFeature> {
+        self.feature
+    }
+    pub fn set_feature(&mut self, feature: Option<CellFeature>) {
+        self.feature = feature;
+    }
 }
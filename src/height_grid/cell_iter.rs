@@ -1,33 +1,74 @@
 use bevy::prelude::*;
 
+use super::coord::Coord;
+
+/// The cells from `min` up to but not including `max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct CellRect {
-    min: UVec2,
-    max: UVec2,
+    min: Coord,
+    max: Coord,
 }
 
 impl CellRect {
-    pub fn new(min: impl Into<UVec2>, max: impl Into<UVec2>) -> Self {
+    pub fn new(min: impl Into<Coord>, max: impl Into<Coord>) -> Self {
         let min = min.into();
         let max = max.into();
-        assert!(min.x <= max.x);
-        assert!(min.y <= max.y);
+        assert!(min.x() <= max.x());
+        assert!(min.y() <= max.y());
         Self { min, max }
     }
 
-    pub fn from_center(center: impl Into<UVec2>, extents: impl Into<UVec2>) -> Self {
+    pub fn from_center(center: impl Into<Coord>, extents: impl Into<UVec2>) -> Self {
         let center = center.into();
         let extents = extents.into();
-        let bottom_left = center.saturating_sub(extents);
-        let top_right = center.saturating_add(extents).saturating_add(UVec2::ONE);
+        let bottom_left = center.as_uvec2().saturating_sub(extents);
+        let top_right = center
+            .as_uvec2()
+            .saturating_add(extents)
+            .saturating_add(UVec2::ONE);
         Self::new(bottom_left, top_right)
     }
 
+    /// The rect containing only `coord`.
+    pub fn single(coord: impl Into<Coord>) -> Self {
+        let coord = coord.into();
+        Self::new(coord, coord.as_uvec2().saturating_add(UVec2::ONE))
+    }
+
+    /// The smallest rect containing both rects.
+    pub fn union(&self, other: &CellRect) -> Self {
+        Self::new(
+            UVec2::min(self.min.as_uvec2(), other.min.as_uvec2()),
+            UVec2::max(self.max.as_uvec2(), other.max.as_uvec2()),
+        )
+    }
+
+    /// The rect grown by `amount` cells on every side, without going past `0` and `limit`.
+    pub fn grow(&self, amount: u32, limit: impl Into<UVec2>) -> Self {
+        Self::new(
+            self.min.as_uvec2().saturating_sub(UVec2::splat(amount)),
+            self.max.as_uvec2().saturating_add(UVec2::splat(amount)),
+        )
+        .clip(limit)
+    }
+
+    /// The part of the rect below `limit`, e.g. the cells that are part of a grid.
+    pub fn clip(&self, limit: impl Into<UVec2>) -> Self {
+        let max = UVec2::min(self.max.as_uvec2(), limit.into());
+        Self::new(UVec2::min(self.min.as_uvec2(), max), max)
+    }
+
+    pub fn contains(&self, coord: impl Into<Coord>) -> bool {
+        let coord = coord.into().as_uvec2();
+        coord.cmpge(self.min.as_uvec2()).all() && coord.cmplt(self.max.as_uvec2()).all()
+    }
+
     pub fn width(&self) -> u32 {
-        self.max.x - self.min.x
+        self.max.x() - self.min.x()
     }
 
     pub fn height(&self) -> u32 {
-        self.max.y - self.min.y
+        self.max.y() - self.min.y()
     }
 
     pub fn num_cells(&self) -> u32 {
@@ -36,7 +77,7 @@ impl CellRect {
 }
 
 impl IntoIterator for CellRect {
-    type Item = UVec2;
+    type Item = Coord;
 
     type IntoIter = CellRectIter;
 
@@ -59,7 +100,7 @@ impl From<CellRect> for CellRectIter {
     }
 }
 impl Iterator for CellRectIter {
-    type Item = UVec2;
+    type Item = Coord;
 
     fn next(&mut self) -> Option<Self::Item> {
         let current = self.current;
@@ -68,11 +109,11 @@ impl Iterator for CellRectIter {
             None
         } else {
             let width = self.rect.width();
-            let y = self.rect.min.y + current / width;
-            let x = self.rect.min.x + current % width;
+            let y = self.rect.min.y() + current / width;
+            let x = self.rect.min.x() + current % width;
 
             self.current += 1;
-            Some((x, y).into())
+            Some(Coord::new(x, y))
         }
     }
 
@@ -86,17 +127,78 @@ impl Iterator for CellRectIter {
     }
 }
 
-pub fn inside_circle(origin: impl Into<UVec2>, radius: u32) -> impl Fn(UVec2) -> bool {
-    let origin = origin.into().as_ivec2();
-    let radius = radius * radius;
-    move |pos| (pos.as_ivec2() - origin).length_squared() <= radius as i32
+/// Iterates the cells around `center` ring by ring, starting with `center` itself and stopping
+/// after the ring `radius` cells away. Every ring starts right below its top right cell and goes
+/// around counterclockwise. Cells outside of the `u32` range are skipped.
+pub struct Spiral {
+    center: Coord,
+    radius: u32,
+    ring: u32,
+    current: u32,
+}
+
+impl Spiral {
+    pub fn new(center: impl Into<Coord>, radius: u32) -> Self {
+        Self {
+            center: center.into(),
+            radius,
+            ring: 0,
+            current: 0,
+        }
+    }
+
+    /// The offset of the `current`th cell of the ring `ring` cells away from the center.
+    fn offset(ring: u32, current: u32) -> IVec2 {
+        let ring = ring as i32;
+        let side = current as i32 / (2 * ring);
+        let step = current as i32 % (2 * ring);
+        match side {
+            0 => IVec2::new(ring, -ring + 1 + step),
+            1 => IVec2::new(ring - 1 - step, ring),
+            2 => IVec2::new(-ring, ring - 1 - step),
+            _ => IVec2::new(-ring + 1 + step, -ring),
+        }
+    }
+}
+
+impl Iterator for Spiral {
+    type Item = Coord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.ring > self.radius {
+                return None;
+            }
+            if self.ring == 0 {
+                self.ring = 1;
+                return Some(self.center);
+            }
+
+            let offset = Self::offset(self.ring, self.current);
+            self.current += 1;
+            if self.current == 8 * self.ring {
+                self.ring += 1;
+                self.current = 0;
+            }
+            if let Some(coord) = self.center.checked_offset(offset) {
+                return Some(coord);
+            }
+        }
+    }
+}
+
+pub fn inside_circle(origin: impl Into<Coord>, radius: u32) -> impl Fn(Coord) -> bool {
+    let origin = origin.into().as_uvec2().as_i64vec2();
+    // Squared in 64 bits, so that large radii do not overflow.
+    let radius = u64::from(radius).pow(2);
+    move |pos| (pos.as_uvec2().as_i64vec2() - origin).length_squared() as u64 <= radius
 }
 
 #[cfg(test)]
 mod tests {
     use crate::height_grid::cell_iter::inside_circle;
 
-    use super::{CellRect, CellRectIter};
+    use super::{CellRect, CellRectIter, Spiral};
 
     #[test]
     #[should_panic]
@@ -185,6 +287,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn union_and_grow_work() {
+        let rect = CellRect::single((1, 1)).union(&CellRect::single((3, 2)));
+        assert_eq!(rect, CellRect::new((1, 1), (4, 3)));
+
+        assert_eq!(rect.grow(2, (5, 5)), CellRect::new((0, 0), (5, 5)));
+    }
+
     #[test]
     fn inside_circle_works() {
         assert!(inside_circle((5, 5), 1)((5, 5).into()));
@@ -200,4 +310,25 @@ mod tests {
         assert!(!inside_circle((5, 5), 1)((4, 6).into()));
         assert!(!inside_circle((5, 5), 1)((6, 4).into()));
     }
+
+    #[test]
+    fn spiral_covers_the_square() {
+        let spiral: Vec<_> = Spiral::new((2, 2), 2).collect();
+        let mut sorted = spiral.clone();
+        sorted.sort();
+
+        assert_eq!(spiral[0], (2, 2).into());
+        assert_eq!(spiral[1], (3, 2).into());
+        assert_eq!(
+            sorted,
+            CellRect::from_center((2, 2), (2, 2))
+                .into_iter()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn spiral_skips_cells_below_zero() {
+        assert_eq!(Spiral::new((0, 0), 1).count(), 4);
+    }
 }
@@ -1,5 +1,12 @@
-use super::cell::Cell;
-use super::corner::Corner;
+use std::collections::HashSet;
+
+use super::cell::{Cell, CellFeature, CellMeshType, Diagonal};
+use super::cell_iter::CellRect;
+use super::coord::Coord;
+use super::corner::{Corner, CORNERS};
+use super::direction::Direction;
+use super::events::ChangeKind;
+use super::vertex::Vertex;
 use bevy::prelude::*;
 
 /// A grid where each cell contains 4 height values, one for each of its corners.
@@ -12,15 +19,24 @@ use bevy::prelude::*;
 /// |   |   |
 /// |   |   |
 /// 2--3,6--7
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
 pub struct HeightGrid {
     pub cells_count: UVec2,
-    pub cells: Box<[Cell]>,
+    pub cells: Vec<Cell>,
+    /// Keeps the corners meeting at a vertex at the same height when editing through the
+    /// methods of the grid, so that the terrain has no cliffs. See [`HeightGrid::validate`].
+    pub welded: bool,
+    /// Edits made through the methods of the grid since the last [`HeightGridChanged`] events.
+    ///
+    /// [`HeightGridChanged`]: super::events::HeightGridChanged
+    #[reflect(ignore)]
+    changes: Vec<(CellRect, ChangeKind)>,
 }
 
 impl HeightGrid {
-    pub fn new(cells_count: impl Into<UVec2>, cells: impl Into<Box<[Cell]>>) -> Self {
-        let cells: Box<[Cell]> = cells.into();
+    pub fn new(cells_count: impl Into<UVec2>, cells: impl Into<Vec<Cell>>) -> Self {
+        let cells: Vec<Cell> = cells.into();
         let cells_count = cells_count.into();
         let UVec2 {
             x: cells_width,
@@ -30,27 +46,45 @@ impl HeightGrid {
         assert!(cells_depth > 0);
         assert!(!cells.is_empty());
         assert_eq!((cells_width * cells_depth) as usize, cells.len());
-        Self { cells_count, cells }
+        Self {
+            cells_count,
+            cells,
+            welded: false,
+            changes: vec![],
+        }
     }
 
-    pub fn valid_coord(&self, coord: impl Into<UVec2>) -> bool {
+    /// All cells of the grid.
+    pub fn bounds(&self) -> CellRect {
+        CellRect::new(UVec2::ZERO, self.cells_count)
+    }
+
+    pub fn valid_coord(&self, coord: impl Into<Coord>) -> bool {
         let coord = coord.into();
-        coord.x < self.cells_count.x && coord.y < self.cells_count.y
+        coord.x() < self.cells_count.x && coord.y() < self.cells_count.y
     }
 
-    pub fn get_cell_index(&self, cell: impl Into<UVec2>) -> usize {
+    /// The neighbouring cell in `direction`, if it is part of the grid.
+    pub fn neighbour(&self, coord: impl Into<Coord>, direction: Direction) -> Option<Coord> {
+        coord
+            .into()
+            .step(direction)
+            .filter(|&neighbour| self.valid_coord(neighbour))
+    }
+
+    pub fn get_cell_index(&self, cell: impl Into<Coord>) -> usize {
         let UVec2 {
             x: cells_width,
             y: cells_depth,
         } = self.cells_count;
-        let UVec2 { x, y } = cell.into();
+        let UVec2 { x, y } = cell.into().as_uvec2();
         assert!(x < cells_width);
         assert!(y < cells_depth);
 
         (cells_width * y + x) as usize
     }
 
-    pub fn try_get_cell(&self, coord: impl Into<UVec2>) -> Option<&Cell> {
+    pub fn try_get_cell(&self, coord: impl Into<Coord>) -> Option<&Cell> {
         let coord = coord.into();
         if !self.valid_coord(coord) {
             return None;
@@ -58,29 +92,353 @@ impl HeightGrid {
 
         return Some(self.get_cell(coord));
     }
-    pub fn get_cell(&self, coord: impl Into<UVec2>) -> &Cell {
+    pub fn get_cell(&self, coord: impl Into<Coord>) -> &Cell {
         let cell_index = self.get_cell_index(coord);
 
         self.cells.get(cell_index).expect("index out of bounds")
     }
 
-    pub fn get_cell_mut(&mut self, coord: impl Into<UVec2>) -> &mut Cell {
+    /// Sets a single corner, or every corner of its vertex if the grid is [`welded`].
+    ///
+    /// [`welded`]: HeightGrid::welded
+    pub fn set_height(&mut self, coord: impl Into<Coord>, corner: Corner, height: u32) {
+        self.update_cell(coord.into(), |cell| cell.set_height(corner, height));
+    }
+
+    pub fn set_void(&mut self, coord: impl Into<Coord>, void: bool) {
+        self.update_cell(coord.into(), |cell| cell.set_void(void));
+    }
+
+    pub fn set_diagonal(&mut self, coord: impl Into<Coord>, diagonal: Option<Diagonal>) {
+        self.update_cell(coord.into(), |cell| cell.set_diagonal(diagonal));
+    }
+
+    pub fn set_feature(&mut self, coord: impl Into<Coord>, feature: Option<CellFeature>) {
+        self.update_cell(coord.into(), |cell| cell.set_feature(feature));
+    }
+
+    /// Replaces everything about a cell.
+    pub fn set_cell(&mut self, coord: impl Into<Coord>, cell: Cell) {
+        self.update_cell(coord.into(), |existing| *existing = cell);
+    }
+
+    /// Calls `modify` with every cell of `region` that is part of the grid. Changed corners are
+    /// welded to their neighbours if the grid is [`welded`], but only after every cell was
+    /// modified, so that `modify` sees the heights from before the edit.
+    ///
+    /// [`welded`]: HeightGrid::welded
+    pub fn modify_region(
+        &mut self,
+        region: impl IntoIterator<Item = Coord>,
+        mut modify: impl FnMut(Coord, &mut Cell),
+    ) {
+        let mut changed = vec![];
+        for coord in region {
+            if self.valid_coord(coord) {
+                changed.extend(self.apply_update(coord, |cell| modify(coord, cell)));
+            }
+        }
+        self.weld(changed);
+    }
+
+    /// Every vertex of the grid, row by row.
+    pub fn vertices(&self) -> impl Iterator<Item = Vertex> {
+        let UVec2 { x: width, y: depth } = self.cells_count;
+        (0..=depth).flat_map(move |y| (0..=width).map(move |x| Vertex::new(x, y)))
+    }
+
+    /// The corners of the cells of the grid meeting at a vertex, up to four.
+    pub fn vertex_corners(
+        &self,
+        vertex: impl Into<Vertex>,
+    ) -> impl Iterator<Item = (Coord, Corner)> + '_ {
+        vertex
+            .into()
+            .corners()
+            .filter(|&(coord, _)| self.valid_coord(coord))
+    }
+
+    /// Position of a vertex at the highest surface meeting there, `None` if the vertex is not
+    /// part of the grid or only touches void cells.
+    pub fn vertex_position(&self, vertex: impl Into<Vertex>) -> Option<Vec3> {
+        let vertex = vertex.into();
+        let height = self
+            .vertex_corners(vertex)
+            .filter(|&(coord, _)| !self.get_cell(coord).is_void())
+            .map(|(coord, corner)| self.get_height(coord, corner))
+            .max()?;
+        Some(vertex.position().extend(height as f32))
+    }
+
+    /// Sets every corner meeting at `vertex`.
+    pub fn set_vertex(&mut self, vertex: impl Into<Vertex>, height: u32) {
+        let corners: Vec<_> = self.vertex_corners(vertex).collect();
+        for (coord, corner) in corners {
+            let cell_index = self.get_cell_index(coord);
+            if self.cells[cell_index].get_height(corner) != height {
+                self.cells[cell_index].set_height(corner, height);
+                self.record_change(CellRect::single(coord), ChangeKind::Heights);
+            }
+        }
+    }
+
+    /// Raises every corner meeting at `vertex` by `delta`, keeping the steps between them. On a
+    /// [`welded`] grid all corners end up at the highest one raised by `delta`.
+    ///
+    /// [`welded`]: HeightGrid::welded
+    pub fn raise_vertex(&mut self, vertex: impl Into<Vertex>, delta: i32) {
+        let vertex = vertex.into();
+        let corners: Vec<_> = self.vertex_corners(vertex).collect();
+        if self.welded {
+            let highest = corners
+                .iter()
+                .map(|&(coord, corner)| self.get_cell(coord).get_height(corner))
+                .max();
+            if let Some(highest) = highest {
+                self.set_vertex(vertex, highest.saturating_add_signed(delta));
+            }
+            return;
+        }
+        for (coord, corner) in corners {
+            let height = self.get_cell(coord).get_height(corner);
+            self.set_height(coord, corner, height.saturating_add_signed(delta));
+        }
+    }
+
+    /// Every vertex whose corners are at different heights, which a [`welded`] grid must not
+    /// have. Void cells are ignored.
+    ///
+    /// [`welded`]: HeightGrid::welded
+    pub fn validate(&self) -> Vec<Seam> {
+        self.vertices()
+            .filter_map(|vertex| {
+                let heights = self
+                    .vertex_corners(vertex)
+                    .filter(|&(coord, _)| !self.get_cell(coord).is_void())
+                    .map(|(coord, corner)| self.get_cell(coord).get_height(corner));
+                let (lowest, highest) = heights.fold((u32::MAX, 0), |(lowest, highest), height| {
+                    (lowest.min(height), highest.max(height))
+                });
+                (lowest < highest).then_some(Seam {
+                    vertex,
+                    lowest,
+                    highest,
+                })
+            })
+            .collect()
+    }
+
+    /// Replaces all cells with those of `other`, which may have a different size.
+    pub fn replace(&mut self, other: HeightGrid) {
+        self.cells_count = other.cells_count;
+        self.cells = other.cells;
+        self.changes.clear();
+        self.record_change(self.bounds(), ChangeKind::Cells);
+    }
+
+    /// The edits since the last call, at most one region per kind.
+    pub fn take_changes(&mut self) -> Vec<(CellRect, ChangeKind)> {
+        std::mem::take(&mut self.changes)
+    }
+
+    fn update_cell(&mut self, coord: Coord, update: impl FnOnce(&mut Cell)) {
+        let changed = self.apply_update(coord, update);
+        self.weld(changed);
+    }
+
+    /// Updates a single cell without welding it, returning the corners whose height changed.
+    fn apply_update(
+        &mut self,
+        coord: Coord,
+        update: impl FnOnce(&mut Cell),
+    ) -> Vec<(Coord, Corner)> {
         let cell_index = self.get_cell_index(coord);
+        let cell = self.cells.get_mut(cell_index).expect("index out of bounds");
+        let before = *cell;
+        update(cell);
+        let after = *cell;
+        let Some(kind) = change_kind(&before, &after) else {
+            return vec![];
+        };
+        self.record_change(CellRect::single(coord), kind);
+
+        CORNERS
+            .into_iter()
+            .filter(|&corner| after.get_height(corner) != before.get_height(corner))
+            .map(|corner| (coord, corner))
+            .collect()
+    }
+
+    /// Moves every vertex with a changed corner to the height of that corner if the grid is
+    /// [`welded`]. Each vertex is welded once, to the first of its changed corners.
+    ///
+    /// [`welded`]: HeightGrid::welded
+    fn weld(&mut self, changed: Vec<(Coord, Corner)>) {
+        if !self.welded {
+            return;
+        }
+        let mut welded = HashSet::new();
+        for (coord, corner) in changed {
+            let vertex = Vertex::from((coord, corner));
+            if welded.insert(vertex) {
+                let height = self.get_cell(coord).get_height(corner);
+                self.set_vertex(vertex, height);
+            }
+        }
+    }
+
+    fn record_change(&mut self, region: CellRect, kind: ChangeKind) {
+        match self.changes.iter_mut().find(|(_, other)| *other == kind) {
+            Some((existing, _)) => *existing = existing.union(&region),
+            None => self.changes.push((region, kind)),
+        }
+    }
+
+    /// Height of a corner of the terrain surface. Unlike [`Cell::get_height`] this follows ramps
+    /// and stairs, whose edge towards their neighbour takes on the neighbour's heights.
+    pub fn get_height(&self, coord: impl Into<Coord>, corner: Corner) -> u32 {
+        let coord = coord.into();
+        let cell = self.get_cell(coord);
+        let Some(direction) = cell.feature().map(|feature| feature.direction()) else {
+            return cell.get_height(corner);
+        };
+        let neighbour = self
+            .neighbour(coord, direction)
+            .map(|neighbour| self.get_cell(neighbour))
+            .filter(|neighbour| !neighbour.is_void());
 
-        self.cells.get_mut(cell_index).expect("index out of bounds")
+        let (a, b) = direction.corners();
+        let (opp_a, opp_b) = direction.opposite().corners();
+        match neighbour {
+            Some(neighbour) if corner == a => neighbour.get_height(opp_a),
+            Some(neighbour) if corner == b => neighbour.get_height(opp_b),
+            _ => cell.get_height(corner),
+        }
     }
-    pub fn get_position(&self, coord: impl Into<UVec2>, corner: Corner) -> Vec3 {
+
+    pub fn get_position(&self, coord: impl Into<Coord>, corner: Corner) -> Vec3 {
         let coord = coord.into();
-        let cell_data = self.get_cell(coord);
-        let height = cell_data.get_height(corner);
+        let height = self.get_height(coord, corner);
 
         let (col_offset, row_offset) = corner.get_corner_offset();
         Vec3::new(
-            coord.x as f32 + col_offset,
-            coord.y as f32 + row_offset,
+            coord.x() as f32 + col_offset,
+            coord.y() as f32 + row_offset,
             height as f32,
         )
     }
+
+    /// Height of the terrain surface at `position`, following the same triangulation as the
+    /// ground mesh. Returns `None` outside of the grid and above void cells.
+    pub fn sample_height(&self, position: Vec2) -> Option<f32> {
+        if position.x < 0.0 || position.y < 0.0 {
+            return None;
+        }
+        let coord = position
+            .floor()
+            .as_uvec2()
+            .min(self.cells_count.saturating_sub(UVec2::ONE));
+        if !self.valid_coord(coord) || position.cmpgt(self.cells_count.as_vec2()).any() {
+            return None;
+        }
+        if self.get_cell(coord).is_void() {
+            return None;
+        }
+
+        Some(self.sample_cell_height(coord, position))
+    }
+
+    /// Height of the surface of a single cell at `position`, which is clamped into the cell. On
+    /// cell edges this picks the side of `coord` even if the neighbour is at a different height.
+    /// Stairs are sampled as if they were a ramp.
+    pub fn sample_cell_height(&self, coord: impl Into<Coord>, position: Vec2) -> f32 {
+        let coord = coord.into();
+        let Vec2 { x, y } = (position - coord.as_vec2()).clamp(Vec2::ZERO, Vec2::ONE);
+        let [tl, tr, bl, br] = [
+            Corner::TopLeft,
+            Corner::TopRight,
+            Corner::BottomLeft,
+            Corner::BottomRight,
+        ]
+        .map(|corner| self.get_height(coord, corner) as f32);
+
+        match self.cell_type(coord) {
+            CellMeshType::Shared => tl,
+            CellMeshType::Slash if y >= x => bl + (tr - tl) * x + (tl - bl) * y,
+            CellMeshType::Slash => bl + (br - bl) * x + (tr - br) * y,
+            CellMeshType::Backslash if x + y <= 1.0 => bl + (br - bl) * x + (tl - bl) * y,
+            CellMeshType::Backslash => tr + (tr - tl) * (x - 1.0) + (tr - br) * (y - 1.0),
+        }
+    }
+
+    /// How a cell is triangulated, honouring its diagonal override unless the cell is flat.
+    pub fn cell_type(&self, cell: impl Into<Coord>) -> CellMeshType {
+        let cell = cell.into();
+        match (
+            self.automatic_cell_type(cell),
+            self.get_cell(cell).diagonal(),
+        ) {
+            (CellMeshType::Shared, _) => CellMeshType::Shared,
+            (_, Some(diagonal)) => diagonal.into(),
+            (automatic, None) => automatic,
+        }
+    }
+
+    /// How a cell is triangulated based on its heights alone.
+    pub fn automatic_cell_type(&self, cell: impl Into<Coord>) -> CellMeshType {
+        let cell = cell.into();
+        let tl = self.get_height(cell, Corner::TopLeft);
+        let tr = self.get_height(cell, Corner::TopRight);
+        let bl = self.get_height(cell, Corner::BottomLeft);
+        let br = self.get_height(cell, Corner::BottomRight);
+
+        let backslash_equal = tl == br;
+        let slash_equal = bl == tr;
+
+        if backslash_equal && slash_equal {
+            use std::cmp::Ordering::*;
+            match tl.cmp(&tr) {
+                Less => CellMeshType::Slash,
+                Equal => CellMeshType::Shared,
+                Greater => CellMeshType::Backslash,
+            }
+        } else if backslash_equal {
+            CellMeshType::Backslash
+        } else {
+            CellMeshType::Slash
+        }
+    }
+}
+
+/// A vertex whose corners are not at the same height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Seam {
+    pub vertex: Vertex,
+    pub lowest: u32,
+    pub highest: u32,
+}
+
+/// How a cell changed, [`ChangeKind::Cells`] if more than one thing changed.
+fn change_kind(before: &Cell, after: &Cell) -> Option<ChangeKind> {
+    let mut kinds = [
+        (
+            CORNERS
+                .iter()
+                .any(|&corner| before.get_height(corner) != after.get_height(corner)),
+            ChangeKind::Heights,
+        ),
+        (before.is_void() != after.is_void(), ChangeKind::Void),
+        (before.diagonal() != after.diagonal(), ChangeKind::Diagonal),
+        (before.feature() != after.feature(), ChangeKind::Feature),
+    ]
+    .into_iter()
+    .filter_map(|(changed, kind)| changed.then_some(kind));
+
+    match (kinds.next(), kinds.next()) {
+        (None, _) => None,
+        (Some(kind), None) => Some(kind),
+        (Some(_), Some(_)) => Some(ChangeKind::Cells),
+    }
 }
 
 #[cfg(test)]
@@ -146,6 +504,18 @@ mod tests {
         assert_eq!(grid.try_get_cell((2, 2)), None);
     }
 
+    #[test]
+    fn neighbour_works() {
+        let grid = HeightGrid::new((2, 2), vec![(0, 0, 0, 0).into(); 4]);
+
+        assert_eq!(
+            grid.neighbour((0, 0), Direction::Top),
+            Some(Coord::new(0, 1))
+        );
+        assert_eq!(grid.neighbour((0, 0), Direction::Left), None);
+        assert_eq!(grid.neighbour((1, 1), Direction::Right), None);
+    }
+
     #[test]
     #[should_panic]
     fn get_cell_panics() {
@@ -178,4 +548,145 @@ mod tests {
             Vec3::new(0.0, 1.0, 2.0)
         );
     }
+
+    #[test]
+    fn sample_height_follows_triangles() {
+        let grid = HeightGrid::new((2, 1), [(2, 2, 2, 2).into(), (2, 0, 0, 0).into()]);
+
+        assert_eq!(grid.sample_height(Vec2::new(0.5, 0.5)), Some(2.0));
+        assert_eq!(grid.sample_height(Vec2::new(1.0, 1.0)), Some(2.0));
+        assert_eq!(grid.sample_height(Vec2::new(1.25, 0.75)), Some(1.0));
+        assert_eq!(grid.sample_height(Vec2::new(1.5, 0.5)), Some(0.0));
+        assert_eq!(grid.sample_height(Vec2::new(1.75, 0.25)), Some(0.0));
+        assert_eq!(grid.sample_height(Vec2::new(2.0, 0.0)), Some(0.0));
+        assert_eq!(grid.sample_height(Vec2::new(2.5, 0.5)), None);
+        assert_eq!(grid.sample_height(Vec2::new(-0.5, 0.5)), None);
+    }
+
+    #[test]
+    fn sample_height_honours_diagonal_override() {
+        // A saddle where the automatic split runs from the bottom left to the top right.
+        let mut grid = HeightGrid::new((1, 1), [(0, 2, 2, 0).into()]);
+        assert_eq!(grid.sample_height(Vec2::new(0.5, 0.5)), Some(2.0));
+
+        grid.set_diagonal((0, 0), Some(Diagonal::Backslash));
+        assert_eq!(grid.sample_height(Vec2::new(0.5, 0.5)), Some(0.0));
+    }
+
+    #[test]
+    fn ramps_take_on_neighbour_heights() {
+        let mut grid = HeightGrid::new((2, 1), [(0, 0, 0, 0).into(), (2, 2, 2, 2).into()]);
+        grid.set_feature((0, 0), Some(CellFeature::Ramp(Direction::Right)));
+
+        assert_eq!(grid.get_height((0, 0), Corner::TopRight), 2);
+        assert_eq!(grid.get_height((0, 0), Corner::BottomRight), 2);
+        assert_eq!(grid.get_height((0, 0), Corner::TopLeft), 0);
+        assert_eq!(grid.sample_height(Vec2::new(0.5, 0.5)), Some(1.0));
+        assert_eq!(grid.get_cell((0, 0)).get_height(Corner::TopRight), 0);
+    }
+
+    #[test]
+    fn sample_height_skips_void_cells() {
+        let mut grid = HeightGrid::new((2, 1), vec![(1, 1, 1, 1).into(); 2]);
+        grid.set_void((1, 0), true);
+
+        assert_eq!(grid.sample_height(Vec2::new(0.5, 0.5)), Some(1.0));
+        assert_eq!(grid.sample_height(Vec2::new(1.5, 0.5)), None);
+    }
+
+    #[test]
+    fn reflect_round_trip() {
+        let mut grid = HeightGrid::new((2, 1), [(0, 1, 2, 3).into(), (4, 4, 4, 4).into()]);
+        grid.set_void((1, 0), true);
+
+        let reflected = grid.clone_value();
+        let copy = HeightGrid::from_reflect(&*reflected).unwrap();
+
+        assert_eq!(copy.cells_count, grid.cells_count);
+        assert_eq!(copy.cells, grid.cells);
+    }
+
+    #[test]
+    fn vertices_move_all_their_corners() {
+        let mut grid = HeightGrid::new((2, 2), vec![(0, 0, 0, 0).into(); 4]);
+        assert_eq!(grid.vertex_corners((1, 1)).count(), 4);
+        assert_eq!(grid.vertex_corners((2, 0)).count(), 1);
+
+        grid.set_height((0, 0), Corner::TopRight, 2);
+        grid.raise_vertex((1, 1), 1);
+        assert_eq!(grid.get_cell((0, 0)).get_height(Corner::TopRight), 3);
+        assert_eq!(grid.get_cell((1, 1)).get_height(Corner::BottomLeft), 1);
+
+        grid.set_vertex((1, 1), 5);
+        assert!(grid.validate().is_empty());
+        assert_eq!(grid.vertex_position((1, 1)), Some(Vec3::new(1.0, 1.0, 5.0)));
+        assert_eq!(grid.vertex_position((3, 0)), None);
+        assert_eq!(grid.vertices().count(), 9);
+    }
+
+    #[test]
+    fn welded_grids_keep_vertices_together() {
+        let mut grid = HeightGrid::new((2, 2), vec![(0, 0, 0, 0).into(); 4]);
+        grid.welded = true;
+
+        grid.set_height((0, 0), Corner::TopRight, 2);
+        assert_eq!(grid.get_cell((1, 1)).get_height(Corner::BottomLeft), 2);
+
+        grid.set_cell((1, 0), (1, 1, 1, 1).into());
+        grid.modify_region(CellRect::new((0, 1), (5, 5)), |_, cell| {
+            cell.set_height(Corner::TopLeft, 4)
+        });
+        assert_eq!(grid.get_cell((0, 0)).get_height(Corner::BottomRight), 1);
+        assert!(grid.validate().is_empty());
+    }
+
+    #[test]
+    fn welded_relative_edits_do_not_stack() {
+        let mut grid = HeightGrid::new((3, 3), vec![(0, 0, 0, 0).into(); 9]);
+        grid.welded = true;
+
+        grid.modify_region(CellRect::new((0, 0), (2, 2)), |_, cell| {
+            for corner in CORNERS {
+                cell.set_height(corner, cell.get_height(corner) + 1);
+            }
+        });
+
+        for coord in CellRect::new((0, 0), (2, 2)) {
+            for corner in CORNERS {
+                assert_eq!(grid.get_cell(coord).get_height(corner), 1);
+            }
+        }
+        assert_eq!(grid.get_cell((2, 2)).get_height(Corner::BottomLeft), 1);
+        assert_eq!(grid.get_cell((2, 2)).get_height(Corner::TopRight), 0);
+        assert!(grid.validate().is_empty());
+    }
+
+    #[test]
+    fn validate_reports_seams() {
+        let mut grid = HeightGrid::new((2, 1), [(0, 0, 0, 0).into(), (2, 2, 2, 2).into()]);
+        assert_eq!(
+            grid.validate(),
+            [Vertex::new(1, 0), Vertex::new(1, 1)].map(|vertex| Seam {
+                vertex,
+                lowest: 0,
+                highest: 2
+            })
+        );
+
+        grid.set_void((1, 0), true);
+        assert!(grid.validate().is_empty());
+    }
+
+    #[test]
+    fn diagonal_override_changes_cell_type() {
+        let mut grid = HeightGrid::new((1, 1), [(2, 1, 0, 3).into()]);
+        assert_eq!(grid.cell_type(Coord::ZERO), CellMeshType::Slash);
+
+        grid.set_diagonal((0, 0), Some(Diagonal::Backslash));
+        assert_eq!(grid.cell_type(Coord::ZERO), CellMeshType::Backslash);
+
+        let mut flat = HeightGrid::new((1, 1), [(1, 1, 1, 1).into()]);
+        flat.set_diagonal((0, 0), Some(Diagonal::Slash));
+        assert_eq!(flat.cell_type(Coord::ZERO), CellMeshType::Shared);
+    }
 }
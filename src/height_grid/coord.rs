@@ -1,34 +1,193 @@
-use bevy::math::UVec2;
+use std::cmp::Ordering;
 
-#[derive(Clone, Copy, Debug, Default, Eq)]
+use bevy::{
+    math::{IVec2, UVec2, Vec2, Vec3},
+    reflect::Reflect,
+    transform::components::GlobalTransform,
+};
+
+use super::direction::{Direction, Direction8, DIRECTIONS, DIRECTIONS_8};
+
+/// The coordinate of a cell. Cell `(x, y)` covers the square from `(x, y)` to `(x + 1, y + 1)`
+/// on the grid plane. Coordinates are ordered row by row, like the cells of a grid.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
 pub struct Coord(UVec2);
 
-impl PartialEq<Coord> for (u32, u32) {
-    fn eq(&self, other: &Coord) -> bool {
-        self.0 == other.0.x && self.1 == other.0.y
+impl Coord {
+    pub const ZERO: Coord = Coord(UVec2::ZERO);
+
+    pub const fn new(x: u32, y: u32) -> Self {
+        Self(UVec2::new(x, y))
+    }
+
+    pub const fn x(&self) -> u32 {
+        self.0.x
+    }
+
+    pub const fn y(&self) -> u32 {
+        self.0.y
+    }
+
+    pub const fn as_uvec2(&self) -> UVec2 {
+        self.0
+    }
+
+    pub fn as_ivec2(&self) -> IVec2 {
+        self.0.as_ivec2()
+    }
+
+    pub fn as_vec2(&self) -> Vec2 {
+        self.0.as_vec2()
+    }
+
+    /// The cell containing `position` on the grid plane, `None` left of or below the origin.
+    pub fn containing(position: Vec2) -> Option<Self> {
+        position
+            .cmpge(Vec2::ZERO)
+            .all()
+            .then(|| Self(position.floor().as_uvec2()))
+    }
+
+    /// The coordinate moved by `offset`, `None` if it would leave the `u32` range.
+    pub fn checked_offset(&self, offset: IVec2) -> Option<Self> {
+        let x = self.0.x.checked_add_signed(offset.x)?;
+        let y = self.0.y.checked_add_signed(offset.y)?;
+        Some(Self::new(x, y))
+    }
+
+    /// The neighbouring coordinate in `direction`, `None` if it would leave the `u32` range.
+    pub fn step(&self, direction: Direction) -> Option<Self> {
+        self.checked_offset(direction.offset())
+    }
+
+    /// The neighbouring coordinate in `direction`, `None` if it would leave the `u32` range.
+    pub fn step8(&self, direction: Direction8) -> Option<Self> {
+        self.checked_offset(direction.offset())
+    }
+
+    /// The up to four coordinates sharing an edge with this one.
+    pub fn neighbours4(&self) -> impl Iterator<Item = Coord> {
+        let coord = *self;
+        DIRECTIONS
+            .into_iter()
+            .filter_map(move |direction| coord.step(direction))
+    }
+
+    /// The up to eight coordinates sharing an edge or a corner with this one.
+    pub fn neighbours8(&self) -> impl Iterator<Item = Coord> {
+        let coord = *self;
+        DIRECTIONS_8
+            .into_iter()
+            .filter_map(move |direction| coord.step8(direction))
+    }
+
+    /// Number of steps to `other` when moving in 4 directions.
+    pub fn manhattan_distance(&self, other: Coord) -> u32 {
+        self.0.x.abs_diff(other.0.x) + self.0.y.abs_diff(other.0.y)
+    }
+
+    /// Number of steps to `other` when moving in 8 directions.
+    pub fn chebyshev_distance(&self, other: Coord) -> u32 {
+        self.0
+            .x
+            .abs_diff(other.0.x)
+            .max(self.0.y.abs_diff(other.0.y))
+    }
+
+    /// The center of the cell on the grid plane.
+    pub fn center(&self) -> Vec2 {
+        self.0.as_vec2() + 0.5
+    }
+
+    /// The center of the cell at `height`, in the world space of the grid at `transform`.
+    pub fn to_world(self, height: f32, transform: &GlobalTransform) -> Vec3 {
+        transform.transform_point(self.center().extend(height))
     }
 }
 
-impl PartialEq<(u32, u32)> for Coord {
-    fn eq(&self, other: &(u32, u32)) -> bool {
-        self.0.x == other.0 && self.0.y == other.1
+impl Ord for Coord {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.0.y, self.0.x).cmp(&(other.0.y, other.0.x))
     }
 }
 
-impl PartialEq<Coord> for Coord {
-    fn eq(&self, other: &Coord) -> bool {
-        self.0.eq(&other.0)
+impl PartialOrd for Coord {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
-impl Into<UVec2> for Coord {
-    fn into(self) -> UVec2 {
-        self.0
+impl From<Coord> for UVec2 {
+    fn from(value: Coord) -> Self {
+        value.0
+    }
+}
+
+impl From<UVec2> for Coord {
+    fn from(value: UVec2) -> Self {
+        Coord(value)
     }
 }
 
-impl Into<Coord> for UVec2 {
-    fn into(self) -> Coord {
-        Coord(self)
+impl From<(u32, u32)> for Coord {
+    fn from((x, y): (u32, u32)) -> Self {
+        Coord::new(x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neighbours_stop_at_zero() {
+        assert_eq!(Coord::ZERO.neighbours4().count(), 2);
+        assert_eq!(Coord::ZERO.neighbours8().count(), 3);
+        assert_eq!(Coord::new(1, 1).neighbours8().count(), 8);
+        assert!(Coord::new(1, 1)
+            .neighbours4()
+            .all(|neighbour| neighbour.manhattan_distance(Coord::new(1, 1)) == 1));
+    }
+
+    #[test]
+    fn distances_work() {
+        let a = Coord::new(1, 5);
+        let b = Coord::new(4, 1);
+        assert_eq!(a.manhattan_distance(b), 7);
+        assert_eq!(a.chebyshev_distance(b), 4);
+    }
+
+    #[test]
+    fn checked_offset_stops_at_the_range() {
+        assert_eq!(
+            Coord::new(2, 0).checked_offset(IVec2::new(-2, 1)),
+            Some(Coord::new(0, 1))
+        );
+        assert_eq!(Coord::new(2, 0).checked_offset(IVec2::new(0, -1)), None);
+        assert_eq!(
+            Coord::new(u32::MAX, 0).checked_offset(IVec2::new(1, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn coords_are_ordered_by_row() {
+        let mut coords = vec![Coord::new(0, 1), Coord::new(1, 0), Coord::new(0, 0)];
+        coords.sort();
+        assert_eq!(coords, [(0, 0), (1, 0), (0, 1)].map(Coord::from));
+    }
+
+    #[test]
+    fn containing_and_center_work() {
+        assert_eq!(
+            Coord::containing(Vec2::new(1.5, 0.2)),
+            Some(Coord::new(1, 0))
+        );
+        assert_eq!(Coord::containing(Vec2::new(-0.1, 0.2)), None);
+        assert_eq!(Coord::new(1, 0).center(), Vec2::new(1.5, 0.5));
+        assert_eq!(
+            Coord::new(1, 0).to_world(2.0, &GlobalTransform::from_xyz(0.0, 0.0, 1.0)),
+            Vec3::new(1.5, 0.5, 3.0)
+        );
     }
 }
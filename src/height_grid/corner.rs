@@ -1,3 +1,5 @@
+use bevy::math::{UVec2, Vec2};
+
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
 pub enum Corner {
     #[default]
@@ -15,12 +17,28 @@ pub const CORNERS: [Corner; 4] = [
 ];
 
 impl Corner {
-    pub fn get_corner_offset(&self) -> (f32, f32) {
+    /// The corner closest to `offset`, a position inside a cell relative to its bottom left.
+    pub fn nearest(offset: Vec2) -> Self {
+        match (offset.x >= 0.5, offset.y >= 0.5) {
+            (false, true) => Corner::TopLeft,
+            (true, true) => Corner::TopRight,
+            (false, false) => Corner::BottomLeft,
+            (true, false) => Corner::BottomRight,
+        }
+    }
+
+    /// The vertex of the corner relative to the bottom left corner of its cell.
+    pub fn offset(&self) -> UVec2 {
         match self {
-            Corner::TopLeft => (0.0, 1.0),
-            Corner::TopRight => (1.0, 1.0),
-            Corner::BottomLeft => (0.0, 0.0),
-            Corner::BottomRight => (1.0, 0.0),
+            Corner::TopLeft => UVec2::new(0, 1),
+            Corner::TopRight => UVec2::new(1, 1),
+            Corner::BottomLeft => UVec2::new(0, 0),
+            Corner::BottomRight => UVec2::new(1, 0),
         }
     }
+
+    pub fn get_corner_offset(&self) -> (f32, f32) {
+        let offset = self.offset().as_vec2();
+        (offset.x, offset.y)
+    }
 }
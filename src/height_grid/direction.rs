@@ -0,0 +1,147 @@
+use bevy::{math::IVec2, reflect::Reflect};
+
+use super::corner::Corner;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Reflect)]
+pub enum Direction {
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+pub const DIRECTIONS: [Direction; 4] = [
+    Direction::Top,
+    Direction::Right,
+    Direction::Bottom,
+    Direction::Left,
+];
+
+impl Direction {
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Top => Direction::Bottom,
+            Direction::Right => Direction::Left,
+            Direction::Bottom => Direction::Top,
+            Direction::Left => Direction::Right,
+        }
+    }
+
+    /// The two corners of a cell on this side, ordered left to right or bottom to top so that
+    /// they line up with the corners of [`Direction::opposite`] in the neighbouring cell.
+    pub fn corners(&self) -> (Corner, Corner) {
+        match self {
+            Direction::Top => (Corner::TopLeft, Corner::TopRight),
+            Direction::Right => (Corner::BottomRight, Corner::TopRight),
+            Direction::Bottom => (Corner::BottomLeft, Corner::BottomRight),
+            Direction::Left => (Corner::BottomLeft, Corner::TopLeft),
+        }
+    }
+
+    /// The offset to the neighbouring cell in this direction.
+    pub fn offset(&self) -> IVec2 {
+        match self {
+            Direction::Top => IVec2::Y,
+            Direction::Right => IVec2::X,
+            Direction::Bottom => IVec2::NEG_Y,
+            Direction::Left => IVec2::NEG_X,
+        }
+    }
+}
+
+/// The directions to all eight cells around a cell, including the diagonal ones.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Direction8 {
+    Top,
+    TopRight,
+    Right,
+    BottomRight,
+    Bottom,
+    BottomLeft,
+    Left,
+    TopLeft,
+}
+
+pub const DIRECTIONS_8: [Direction8; 8] = [
+    Direction8::Top,
+    Direction8::TopRight,
+    Direction8::Right,
+    Direction8::BottomRight,
+    Direction8::Bottom,
+    Direction8::BottomLeft,
+    Direction8::Left,
+    Direction8::TopLeft,
+];
+
+impl Direction8 {
+    /// The offset to the neighbouring cell in this direction.
+    pub fn offset(&self) -> IVec2 {
+        match self {
+            Direction8::Top => IVec2::new(0, 1),
+            Direction8::TopRight => IVec2::new(1, 1),
+            Direction8::Right => IVec2::new(1, 0),
+            Direction8::BottomRight => IVec2::new(1, -1),
+            Direction8::Bottom => IVec2::new(0, -1),
+            Direction8::BottomLeft => IVec2::new(-1, -1),
+            Direction8::Left => IVec2::new(-1, 0),
+            Direction8::TopLeft => IVec2::new(-1, 1),
+        }
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(value: Direction) -> Self {
+        match value {
+            Direction::Top => Direction8::Top,
+            Direction::Right => Direction8::Right,
+            Direction::Bottom => Direction8::Bottom,
+            Direction::Left => Direction8::Left,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::height_grid::coord::Coord;
+
+    #[test]
+    fn corners_line_up_with_opposite() {
+        for direction in DIRECTIONS {
+            let (a, b) = direction.corners();
+            let (opp_a, opp_b) = direction.opposite().corners();
+            let (ax, ay) = a.get_corner_offset();
+            let (bx, by) = b.get_corner_offset();
+            let (oax, oay) = opp_a.get_corner_offset();
+            let (obx, oby) = opp_b.get_corner_offset();
+            let offset = direction.offset().as_vec2();
+
+            assert_eq!((ax - offset.x, ay - offset.y), (oax, oay));
+            assert_eq!((bx - offset.x, by - offset.y), (obx, oby));
+        }
+    }
+
+    #[test]
+    fn step_stops_at_zero() {
+        assert_eq!(Coord::new(0, 3).step(Direction::Left), None);
+        assert_eq!(Coord::new(3, 0).step(Direction::Bottom), None);
+        assert_eq!(
+            Coord::new(3, 0).step(Direction::Top),
+            Some(Coord::new(3, 1))
+        );
+    }
+
+    #[test]
+    fn directions_8_extend_directions() {
+        for direction in DIRECTIONS {
+            assert_eq!(Direction8::from(direction).offset(), direction.offset());
+        }
+        assert_eq!(
+            DIRECTIONS_8
+                .map(|direction| direction.offset())
+                .iter()
+                .sum::<IVec2>(),
+            IVec2::ZERO
+        );
+    }
+}
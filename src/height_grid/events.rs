@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+
+use super::{cell_iter::CellRect, HeightGrid};
+
+/// Sent after the cells of a [`HeightGrid`] changed. Edits through the methods of the grid are
+/// reported with the cells they touched, any other change reports the whole grid as
+/// [`ChangeKind::Cells`]. Values derived from neighbouring cells, like cliffs or the effective
+/// heights of ramps, can change outside of `region`.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeightGridChanged {
+    pub entity: Entity,
+    pub region: CellRect,
+    pub kind: ChangeKind,
+}
+
+/// What changed about the cells of a [`HeightGridChanged`] region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Heights,
+    Void,
+    Diagonal,
+    Feature,
+    /// Anything about the cells, e.g. when the grid was added or replaced.
+    Cells,
+}
+
+/// Sent after the meshes of a [`HeightGrid`] were rebuilt and its colliders were built from them.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TerrainMeshed {
+    pub entity: Entity,
+}
+
+pub(super) fn emit_height_grid_changes(
+    mut height_grid_q: Query<(Entity, &mut HeightGrid), Changed<HeightGrid>>,
+    mut changed: EventWriter<HeightGridChanged>,
+) {
+    for (entity, mut height_grid) in height_grid_q.iter_mut() {
+        let added = height_grid.is_added();
+        let height_grid = height_grid.bypass_change_detection();
+        let changes = height_grid.take_changes();
+        if added || changes.is_empty() {
+            changed.send(HeightGridChanged {
+                entity,
+                region: height_grid.bounds(),
+                kind: ChangeKind::Cells,
+            });
+            continue;
+        }
+        changed.send_batch(changes.into_iter().map(|(region, kind)| HeightGridChanged {
+            entity,
+            region,
+            kind,
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::height_grid::corner::Corner;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_event::<HeightGridChanged>()
+            .add_systems(Update, emit_height_grid_changes);
+        app
+    }
+
+    fn read(app: &App) -> Vec<HeightGridChanged> {
+        let events = app.world().resource::<Events<HeightGridChanged>>();
+        events.get_reader().read(events).copied().collect()
+    }
+
+    #[test]
+    fn added_grids_report_every_cell() {
+        let mut app = app();
+        let grid = HeightGrid::new((2, 2), vec![(0, 0, 0, 0).into(); 4]);
+        let entity = app.world_mut().spawn(grid).id();
+
+        app.update();
+
+        assert_eq!(
+            read(&app),
+            vec![HeightGridChanged {
+                entity,
+                region: CellRect::new((0, 0), (2, 2)),
+                kind: ChangeKind::Cells,
+            }]
+        );
+    }
+
+    #[test]
+    fn edits_report_their_region_and_kind() {
+        let mut app = app();
+        let grid = HeightGrid::new((4, 4), vec![(0, 0, 0, 0).into(); 16]);
+        let entity = app.world_mut().spawn(grid).id();
+        app.update();
+        app.world_mut()
+            .resource_mut::<Events<HeightGridChanged>>()
+            .clear();
+
+        let mut grid = app.world_mut().get_mut::<HeightGrid>(entity).unwrap();
+        grid.set_height((1, 1), Corner::TopLeft, 2);
+        grid.set_height((2, 3), Corner::TopLeft, 2);
+        grid.set_void((0, 0), true);
+        // Setting a value the cell already has is not a change.
+        grid.set_void((3, 3), false);
+        app.update();
+
+        assert_eq!(
+            read(&app),
+            vec![
+                HeightGridChanged {
+                    entity,
+                    region: CellRect::new((1, 1), (3, 4)),
+                    kind: ChangeKind::Heights,
+                },
+                HeightGridChanged {
+                    entity,
+                    region: CellRect::single((0, 0)),
+                    kind: ChangeKind::Void,
+                },
+            ]
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use bevy::math::UVec2;
 
-use super::corner::Corner;
+use super::{coord::Coord, corner::Corner};
 
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub enum FlipAxis {
@@ -10,12 +10,13 @@ pub enum FlipAxis {
 }
 
 pub trait FlipCorner {
-    fn flip(&self, flip: FlipAxis) -> Option<(UVec2, Corner)>;
+    fn flip(&self, flip: FlipAxis) -> Option<(Coord, Corner)>;
 }
 
-impl FlipCorner for (UVec2, Corner) {
-    fn flip(&self, flip: FlipAxis) -> Option<(UVec2, Corner)> {
-        let &(UVec2 { x, y }, corner) = self;
+impl FlipCorner for (Coord, Corner) {
+    fn flip(&self, flip: FlipAxis) -> Option<(Coord, Corner)> {
+        let &(coord, corner) = self;
+        let UVec2 { x, y } = coord.as_uvec2();
         use Corner::*;
         use FlipAxis::*;
 
@@ -41,7 +42,7 @@ impl FlipCorner for (UVec2, Corner) {
         };
 
         match new_corner {
-            ((Some(x), Some(y)), corner) => Some((UVec2 { x, y }, corner)),
+            ((Some(x), Some(y)), corner) => Some((Coord::new(x, y), corner)),
             _ => None,
         }
     }
@@ -0,0 +1,237 @@
+use bevy::prelude::*;
+
+use super::{
+    cell::CellMeshType, coord::Coord, pathfinding::cell_center, region::CellRegion, HeightGrid,
+};
+
+/// How far the terrain may poke through a sight line before it counts as blocked, so that lines
+/// grazing the surface stay visible.
+const TOLERANCE: f32 = 1e-4;
+
+/// Whether the straight line between two points in grid space stays above the terrain.
+///
+/// The terrain inside a cell consists of two planar triangles, so checking the line at every
+/// cell edge and cell diagonal it crosses is exact. Cliffs are covered by checking the line
+/// against both cells at each edge. Void cells have no surface and never block the line.
+pub fn has_line_of_sight(grid: &HeightGrid, from: Vec3, to: Vec3) -> bool {
+    let start = from.xy();
+    let delta = (to - from).xy();
+
+    let Some((t_enter, t_exit)) = clip_to_grid(grid, start, delta, 1.0) else {
+        return true;
+    };
+
+    let mut crossings = vec![t_enter, t_exit];
+    for (origin, direction) in [(start.x, delta.x), (start.y, delta.y)] {
+        if direction == 0.0 {
+            continue;
+        }
+        let a = origin + direction * t_enter;
+        let b = origin + direction * t_exit;
+        let (min, max) = (a.min(b).ceil() as i64, a.max(b).floor() as i64);
+        crossings.extend((min..=max).map(|line| (line as f32 - origin) / direction));
+    }
+    crossings.retain(|t| (t_enter..=t_exit).contains(t));
+    crossings.sort_by(f32::total_cmp);
+    crossings.dedup();
+
+    let visible_at = |coord: Coord, t: f32| {
+        let point = from.lerp(to, t);
+        grid.sample_cell_height(coord, point.xy()) <= point.z + TOLERANCE
+    };
+
+    crossings.windows(2).all(|interval| {
+        let (t0, t1) = (interval[0], interval[1]);
+        let middle = start + delta * (t0 + t1) / 2.0;
+        let coord = Coord::from(
+            middle
+                .floor()
+                .as_uvec2()
+                .min(grid.cells_count.saturating_sub(UVec2::ONE)),
+        );
+        if grid.get_cell(coord).is_void() {
+            return true;
+        }
+
+        let diagonal = diagonal_crossing(grid, coord, start, delta).filter(|t| *t > t0 && *t < t1);
+
+        [t0, t1]
+            .into_iter()
+            .chain(diagonal)
+            .all(|t| visible_at(coord, t))
+    })
+}
+
+/// Clips the segment `start + t * delta` for `t` in `0..=t_end` to the area covered by the grid.
+pub(super) fn clip_to_grid(
+    grid: &HeightGrid,
+    start: Vec2,
+    delta: Vec2,
+    t_end: f32,
+) -> Option<(f32, f32)> {
+    let size = grid.cells_count.as_vec2();
+    let (mut t_enter, mut t_exit) = (0.0f32, t_end);
+
+    for (origin, direction, max) in [(start.x, delta.x, size.x), (start.y, delta.y, size.y)] {
+        if direction == 0.0 {
+            if origin < 0.0 || origin > max {
+                return None;
+            }
+            continue;
+        }
+        let a = (0.0 - origin) / direction;
+        let b = (max - origin) / direction;
+        t_enter = t_enter.max(a.min(b));
+        t_exit = t_exit.min(a.max(b));
+    }
+
+    (t_enter <= t_exit).then_some((t_enter, t_exit))
+}
+
+/// Where the segment crosses the diagonal the cell is split along, if it is not flat.
+fn diagonal_crossing(grid: &HeightGrid, coord: Coord, start: Vec2, delta: Vec2) -> Option<f32> {
+    let local = start - coord.as_vec2();
+    let (numerator, denominator) = match grid.cell_type(coord) {
+        CellMeshType::Shared => return None,
+        CellMeshType::Slash => (local.y - local.x, delta.x - delta.y),
+        CellMeshType::Backslash => (1.0 - local.x - local.y, delta.x + delta.y),
+    };
+    (denominator != 0.0).then(|| numerator / denominator)
+}
+
+/// Whether a unit standing in `from` with its eyes at `eye_height` above the ground can see a
+/// point `target_height` above the ground at the center of `to`. Cells outside of the grid can
+/// neither see nor be seen.
+pub fn can_see_cell(
+    grid: &HeightGrid,
+    from: impl Into<Coord>,
+    to: impl Into<Coord>,
+    eye_height: f32,
+    target_height: f32,
+) -> bool {
+    let (from, to) = (from.into(), to.into());
+    if !grid.valid_coord(from) || !grid.valid_coord(to) {
+        return false;
+    }
+    let eye = cell_center(grid, from) + Vec3::Z * eye_height;
+    let target = cell_center(grid, to) + Vec3::Z * target_height;
+    has_line_of_sight(grid, eye, target)
+}
+
+/// All cells within `radius` of `from` that can be seen from it, see [`can_see_cell`]. Void
+/// cells and cells outside of the grid are neither seen nor can they see.
+pub fn viewshed(
+    grid: &HeightGrid,
+    from: impl Into<Coord>,
+    radius: u32,
+    eye_height: f32,
+    target_height: f32,
+) -> Vec<Coord> {
+    let from = from.into();
+    if !grid.valid_coord(from) || grid.get_cell(from).is_void() {
+        return vec![];
+    }
+    CellRegion::circle(from, radius, grid.cells_count)
+        .into_iter()
+        .filter(|&coord| !grid.get_cell(coord).is_void())
+        .filter(|&coord| can_see_cell(grid, from, coord, eye_height, target_height))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(heights: &[u32]) -> HeightGrid {
+        let cells: Vec<_> = heights.iter().map(|&h| (h, h, h, h).into()).collect();
+        HeightGrid::new((heights.len() as u32, 1), cells)
+    }
+
+    #[test]
+    fn flat_terrain_is_visible() {
+        let grid = row(&[0, 0, 0, 0]);
+
+        assert!(has_line_of_sight(
+            &grid,
+            Vec3::new(0.5, 0.5, 0.0),
+            Vec3::new(3.5, 0.5, 0.0)
+        ));
+    }
+
+    #[test]
+    fn wall_blocks_sight() {
+        let grid = row(&[0, 2, 0]);
+
+        assert!(!can_see_cell(&grid, (0, 0), (2, 0), 1.0, 0.0));
+        assert!(can_see_cell(&grid, (0, 0), (2, 0), 3.0, 3.0));
+    }
+
+    #[test]
+    fn cliff_edge_blocks_sight() {
+        let grid = row(&[0, 0, 3, 3]);
+
+        assert!(!has_line_of_sight(
+            &grid,
+            Vec3::new(0.5, 0.5, 1.0),
+            Vec3::new(3.5, 0.5, 4.0)
+        ));
+    }
+
+    #[test]
+    fn diagonal_ridge_blocks_sight() {
+        // A single cell whose top left and bottom right corners form a ridge.
+        let grid = HeightGrid::new((1, 1), [(2, 0, 0, 2).into()]);
+
+        assert!(!has_line_of_sight(
+            &grid,
+            Vec3::new(0.0, 0.0, 0.5),
+            Vec3::new(1.0, 1.0, 0.5)
+        ));
+        assert!(has_line_of_sight(
+            &grid,
+            Vec3::new(0.0, 0.0, 2.5),
+            Vec3::new(1.0, 1.0, 2.5)
+        ));
+    }
+
+    #[test]
+    fn lines_outside_the_grid_are_visible() {
+        let grid = row(&[5]);
+
+        assert!(has_line_of_sight(
+            &grid,
+            Vec3::new(-1.0, 2.0, 0.0),
+            Vec3::new(3.0, 2.0, 0.0)
+        ));
+    }
+
+    #[test]
+    fn viewshed_stops_at_walls() {
+        let grid = row(&[0, 0, 3, 0, 0]);
+
+        let visible = viewshed(&grid, (0, 0), 4, 1.0, 0.0);
+
+        assert_eq!(visible, vec![Coord::new(0, 0), Coord::new(1, 0)]);
+    }
+
+    #[test]
+    fn void_cells_do_not_block_sight() {
+        let mut grid = row(&[0, 5, 0]);
+        grid.set_void((1, 0), true);
+
+        assert!(can_see_cell(&grid, (0, 0), (2, 0), 1.0, 0.0));
+        assert_eq!(
+            viewshed(&grid, (0, 0), 2, 1.0, 0.0),
+            vec![Coord::new(0, 0), Coord::new(2, 0)]
+        );
+    }
+
+    #[test]
+    fn cells_outside_the_grid_see_nothing() {
+        let grid = row(&[0, 0, 0]);
+
+        assert!(!can_see_cell(&grid, (5, 0), (0, 0), 1.0, 0.0));
+        assert!(!can_see_cell(&grid, (0, 0), (0, 5), 1.0, 0.0));
+        assert!(viewshed(&grid, (5, 0), 4, 1.0, 0.0).is_empty());
+    }
+}
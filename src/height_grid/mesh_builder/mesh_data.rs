@@ -64,6 +64,44 @@ impl MeshData {
 
         self.uvs.extend(uvs);
     }
+
+    pub fn append(&mut self, other: MeshData) {
+        let index_offset: u32 = self
+            .positions
+            .len()
+            .try_into()
+            .expect("must be a valid u32");
+
+        self.positions.extend(other.positions);
+        self.indices
+            .extend(other.indices.into_iter().map(|index| index + index_offset));
+        self.normals.extend(other.normals);
+        self.uvs.extend(other.uvs);
+    }
+}
+
+/// Receives the triangles the mesh builder emits, e.g. to intersect rays with them without
+/// building a mesh.
+pub trait TriangleSink {
+    fn create_triangle(&mut self, vertices: &[Vec3; 3], uvs: &[[f32; 2]; 3]);
+
+    /// Two triangles given as two rows of two vertices, like [`MeshData::create_quad`].
+    fn create_quad(&mut self, vertices: &[Vec3; 4], uvs: &[[f32; 2]; 4]) {
+        let [a, b, c, d] = *vertices;
+        let [uv_a, uv_b, uv_c, uv_d] = *uvs;
+        self.create_triangle(&[a, c, b], &[uv_a, uv_c, uv_b]);
+        self.create_triangle(&[b, c, d], &[uv_b, uv_c, uv_d]);
+    }
+}
+
+impl TriangleSink for MeshData {
+    fn create_triangle(&mut self, vertices: &[Vec3; 3], uvs: &[[f32; 2]; 3]) {
+        MeshData::create_triangle(self, vertices, uvs);
+    }
+
+    fn create_quad(&mut self, vertices: &[Vec3; 4], uvs: &[[f32; 2]; 4]) {
+        MeshData::create_quad(self, vertices, uvs);
+    }
 }
 
 impl From<MeshData> for Mesh {
@@ -124,4 +162,16 @@ mod tests {
         let normal: [f32; 3] = Vec3::Z.into();
         assert_eq!(mesh_data.normals, &[normal, normal, normal, normal]);
     }
+
+    #[test]
+    fn append_offsets_indices() {
+        let mut mesh_data = MeshData::default();
+        mesh_data.create_triangle(&[Vec3::ZERO, Vec3::X, Vec3::Y], &Default::default());
+        let other = mesh_data.clone();
+
+        mesh_data.append(other);
+
+        assert_eq!(mesh_data.positions.len(), 6);
+        assert_eq!(mesh_data.indices, &[0, 1, 2, 3, 4, 5]);
+    }
 }
@@ -1,123 +1,289 @@
 mod mesh_data;
+mod stairs;
 
-use avian3d::prelude::{ColliderConstructor, ColliderConstructorHierarchy};
+use avian3d::prelude::{Collider, ColliderConstructor};
 use bevy::prelude::*;
 use mesh_data::MeshData;
-
-use crate::{Cliffs, Ground};
+pub use mesh_data::TriangleSink;
+use stairs::create_stairs;
 
 use super::flip::*;
-use super::{corner::Corner, HeightGrid};
+use super::{
+    cell::{CellFeature, CellMeshType},
+    coord::Coord,
+    corner::Corner,
+    events::TerrainMeshed,
+    theme::{Base, Cliffs, Ground, TerrainTheme, Water},
+    water::WaterLevel,
+    HeightGrid,
+};
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
 pub struct RequiresMeshing;
 
+/// Optional settings of how the meshes of a [`HeightGrid`] are built. Grids without this
+/// component use the defaults.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct MeshSettings {
+    /// Height the cliffs along void cells reach down to.
+    pub void_floor: f32,
+    /// Walls around the border of the grid, without them the terrain is open to the sides.
+    pub skirt: Option<SkirtSettings>,
+}
+
+impl Default for MeshSettings {
+    fn default() -> Self {
+        Self {
+            void_floor: -2.0,
+            skirt: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct SkirtSettings {
+    /// Height the skirt walls reach down to.
+    pub base_height: f32,
+    /// Close the bottom of the grid at the base height.
+    pub bottom_cap: bool,
+    pub material: SkirtMaterial,
+}
+
+impl Default for SkirtSettings {
+    fn default() -> Self {
+        Self {
+            base_height: -2.0,
+            bottom_cap: true,
+            material: SkirtMaterial::Base,
+        }
+    }
+}
+
+/// Which child of the grid the skirt is part of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum SkirtMaterial {
+    /// Merge the skirt into the cliffs mesh.
+    Cliffs,
+    /// Build a separate mesh for the [`Base`] child.
+    Base,
+}
+
 pub struct MeshBuilderPlugin;
 
 impl Plugin for MeshBuilderPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, generate_meshes);
+        app.add_systems(Update, (remesh_changed_grids, generate_meshes).chain())
+            .add_systems(PostUpdate, announce_built_colliders);
     }
 }
 
-fn generate_meshes(
+/// A grid whose meshes were rebuilt but whose colliders are still being built from them.
+#[derive(Component)]
+struct AwaitingColliders;
+
+/// Grids whose meshes are out of date.
+type ChangedGrid = Or<(Changed<HeightGrid>, Changed<MeshSettings>)>;
+
+/// Remeshes grids edited from anywhere, including the inspector.
+fn remesh_changed_grids(mut commands: Commands, changed_q: Query<Entity, ChangedGrid>) {
+    for entity in changed_q.iter() {
+        commands.entity(entity).insert(RequiresMeshing);
+    }
+}
+
+/// The mesh of a child of a [`HeightGrid`] and which part of the terrain it shows.
+type TerrainMesh = (
+    &'static mut Handle<Mesh>,
+    Has<Ground>,
+    Has<Cliffs>,
+    Has<Water>,
+    Has<Base>,
+);
+
+/// A [`HeightGrid`] together with everything its meshes are built from.
+type MeshedGrid = (
+    Entity,
+    &'static HeightGrid,
+    Option<&'static WaterLevel>,
+    Option<&'static MeshSettings>,
+    Option<&'static TerrainTheme>,
+    &'static Children,
+);
+
+pub(super) fn generate_meshes(
     mut commands: Commands,
-    requires_meshing_q: Query<(Entity, &HeightGrid, &Children), With<RequiresMeshing>>,
+    requires_meshing_q: Query<MeshedGrid, With<RequiresMeshing>>,
     mut meshes: ResMut<Assets<Mesh>>,
-    mut cliffs_q: Query<&mut Handle<Mesh>, (With<Cliffs>, Without<Ground>)>,
-    mut ground_q: Query<&mut Handle<Mesh>, (With<Ground>, Without<Cliffs>)>,
+    mut terrain_q: Query<TerrainMesh>,
+    mut meshed: EventWriter<TerrainMeshed>,
 ) {
-    for (entity, height_grid, children) in requires_meshing_q.iter() {
+    for (entity, height_grid, water_level, settings, theme, children) in requires_meshing_q.iter() {
         info!("Remeshing");
-        let HeightGridMeshes { ground, cliffs } = build(height_grid);
+        let HeightGridMeshes {
+            ground,
+            cliffs,
+            water,
+            base,
+        } = build(
+            height_grid,
+            water_level,
+            &settings.copied().unwrap_or_default(),
+        );
 
         let cliffs = meshes.add(cliffs);
         let ground = meshes.add(ground);
+        let water = meshes.add(water);
+        let base = meshes.add(base);
+        let colliders = theme.map_or(Some(ColliderConstructor::TrimeshFromMesh), |theme| {
+            theme.colliders.clone()
+        });
         for child in children {
-            if let Ok(mut cliffs_handle) = cliffs_q.get_mut(*child) {
-                *cliffs_handle = cliffs.clone();
+            let Ok((mut handle, is_ground, is_cliffs, is_water, is_base)) =
+                terrain_q.get_mut(*child)
+            else {
+                continue;
+            };
+            if is_ground {
+                *handle = ground.clone();
+            } else if is_cliffs {
+                *handle = cliffs.clone();
+            } else if is_water {
+                *handle = water.clone();
+            } else if is_base {
+                *handle = base.clone();
             }
-            if let Ok(mut ground_handle) = ground_q.get_mut(*child) {
-                *ground_handle = ground.clone();
+            // Water is not solid, so it never gets a collider.
+            if let Some(colliders) = colliders.clone().filter(|_| !is_water) {
+                commands
+                    .entity(*child)
+                    .remove::<Collider>()
+                    .insert(colliders);
             }
         }
-        commands
-            .entity(entity)
-            .remove::<RequiresMeshing>()
-            .remove::<ColliderConstructorHierarchy>()
-            .insert(ColliderConstructorHierarchy::new(Some(
-                ColliderConstructor::TrimeshFromMesh,
-            )));
+        let mut grid = commands.entity(entity);
+        grid.remove::<RequiresMeshing>();
+        if colliders.is_some() {
+            grid.insert(AwaitingColliders);
+        } else {
+            grid.remove::<AwaitingColliders>();
+            meshed.send(TerrainMeshed { entity });
+        }
+    }
+}
+
+/// The constructors of the children are removed once their colliders are built.
+fn announce_built_colliders(
+    mut commands: Commands,
+    awaiting_q: Query<(Entity, &Children), With<AwaitingColliders>>,
+    constructor_q: Query<(), With<ColliderConstructor>>,
+    mut meshed: EventWriter<TerrainMeshed>,
+) {
+    for (entity, children) in awaiting_q.iter() {
+        if constructor_q.iter_many(children).next().is_some() {
+            continue;
+        }
+        commands.entity(entity).remove::<AwaitingColliders>();
+        meshed.send(TerrainMeshed { entity });
     }
 }
 
 pub struct HeightGridMeshes {
     pub ground: Mesh,
     pub cliffs: Mesh,
+    pub water: Mesh,
+    /// The skirt and bottom cap, empty unless the skirt uses [`SkirtMaterial::Base`].
+    pub base: Mesh,
 }
 
-pub fn build(height_grid: &HeightGrid) -> HeightGridMeshes {
+pub fn build(
+    height_grid: &HeightGrid,
+    water_level: Option<&WaterLevel>,
+    settings: &MeshSettings,
+) -> HeightGridMeshes {
     let mut ground_mesh_data = MeshData::default();
     let mut cliffs_mesh_data = MeshData::default();
+    let mut water_mesh_data = MeshData::default();
+    let mut base_mesh_data = MeshData::default();
 
     for y in 0..height_grid.cells_count.y {
         for x in 0..height_grid.cells_count.x {
-            let cell = UVec2::new(x, y);
-            let grid = height_grid;
-            let mesh_type = get_cell_type(grid, cell);
-            {
-                match mesh_type {
-                    CellMeshType::Shared => create_flat_cell(grid, &mut ground_mesh_data, cell),
-                    CellMeshType::Slash => {
-                        create_split_cell(grid, &mut ground_mesh_data, cell, true)
-                    }
-                    CellMeshType::Backslash => {
-                        create_split_cell(grid, &mut ground_mesh_data, cell, false)
-                    }
-                };
+            build_cell(
+                height_grid,
+                Coord::new(x, y),
+                settings,
+                &mut ground_mesh_data,
+                &mut cliffs_mesh_data,
+                &mut base_mesh_data,
+            );
+        }
+    }
+
+    if let Some(skirt) = settings.skirt {
+        if skirt.bottom_cap {
+            create_bottom_cap(
+                &mut base_mesh_data,
+                height_grid.cells_count,
+                skirt.base_height,
+            );
+        }
+        if skirt.material == SkirtMaterial::Cliffs {
+            cliffs_mesh_data.append(std::mem::take(&mut base_mesh_data));
+        }
+    }
+
+    if let Some(water_level) = water_level {
+        let surfaces = water_level.surface_heights(height_grid);
+        for y in 0..height_grid.cells_count.y {
+            for x in 0..height_grid.cells_count.x {
+                let cell = Coord::new(x, y);
+                if let Some(surface) = surfaces[height_grid.get_cell_index(cell)] {
+                    create_water_cell(&mut water_mesh_data, cell, surface);
+                }
             }
-            create_cliffs(grid, &mut cliffs_mesh_data, cell);
         }
     }
 
     HeightGridMeshes {
         ground: ground_mesh_data.into(),
         cliffs: cliffs_mesh_data.into(),
+        water: water_mesh_data.into(),
+        base: base_mesh_data.into(),
     }
 }
 
-enum CellMeshType {
-    Shared,
-    Slash,
-    Backslash,
-}
-
-fn get_cell_type(height_grid: &HeightGrid, cell: UVec2) -> CellMeshType {
-    let cell = height_grid.get_cell(cell);
-
-    let tl = cell.get_height(Corner::TopLeft);
-    let tr = cell.get_height(Corner::TopRight);
-    let bl = cell.get_height(Corner::BottomLeft);
-    let br = cell.get_height(Corner::BottomRight);
-
-    let backslash_equal = tl == br;
-    let slash_equal = bl == tr;
-
-    if backslash_equal && slash_equal {
-        use std::cmp::Ordering::*;
-        match tl.cmp(&tr) {
-            Less => CellMeshType::Slash,
-            Equal => CellMeshType::Shared,
-            Greater => CellMeshType::Backslash,
-        }
-    } else if backslash_equal {
-        CellMeshType::Backslash
+/// Emits the ground of `cell` and the cliffs below its edges. Void cells have neither. The skirt
+/// below the edges on the border of the grid goes into `base`.
+pub fn build_cell(
+    grid: &HeightGrid,
+    cell: Coord,
+    settings: &MeshSettings,
+    ground: &mut impl TriangleSink,
+    cliffs: &mut impl TriangleSink,
+    base: &mut impl TriangleSink,
+) {
+    if grid.get_cell(cell).is_void() {
+        return;
+    }
+    if let Some(CellFeature::Stairs(direction)) = grid.get_cell(cell).feature() {
+        create_stairs(grid, ground, cliffs, cell, direction);
     } else {
-        CellMeshType::Slash
+        match grid.cell_type(cell) {
+            CellMeshType::Shared => create_flat_cell(grid, ground, cell),
+            CellMeshType::Slash => create_split_cell(grid, ground, cell, true),
+            CellMeshType::Backslash => create_split_cell(grid, ground, cell, false),
+        };
     }
+    create_cliffs(grid, cliffs, base, cell, settings);
 }
 
-fn create_split_cell(height_grid: &HeightGrid, mesh_data: &mut MeshData, cell: UVec2, slash: bool) {
+fn create_split_cell(
+    height_grid: &HeightGrid,
+    mesh_data: &mut impl TriangleSink,
+    cell: Coord,
+    slash: bool,
+) {
     let tl = height_grid.get_position(cell, Corner::TopLeft);
     let tr = height_grid.get_position(cell, Corner::TopRight);
     let bl = height_grid.get_position(cell, Corner::BottomLeft);
@@ -130,7 +296,7 @@ fn create_split_cell(height_grid: &HeightGrid, mesh_data: &mut MeshData, cell: U
         mesh_data.create_triangle(&[tl, br, tr], &[[0.0, 1.0], [1.0, 0.0], [1.0, 1.0]]);
     };
 }
-fn create_flat_cell(height_grid: &HeightGrid, mesh_data: &mut MeshData, cell: UVec2) {
+fn create_flat_cell(height_grid: &HeightGrid, mesh_data: &mut impl TriangleSink, cell: Coord) {
     let tl = height_grid.get_position(cell, Corner::TopLeft);
     let tr = height_grid.get_position(cell, Corner::TopRight);
     let bl = height_grid.get_position(cell, Corner::BottomLeft);
@@ -141,58 +307,172 @@ fn create_flat_cell(height_grid: &HeightGrid, mesh_data: &mut MeshData, cell: UV
     );
 }
 
-fn create_cliffs(grid: &HeightGrid, mesh_data: &mut MeshData, cell: UVec2) {
+fn create_water_cell(mesh_data: &mut MeshData, cell: Coord, surface: f32) {
+    let bl = cell.as_vec2().extend(surface);
+    mesh_data.create_quad(
+        &[bl + Vec3::Y, bl + Vec3::X + Vec3::Y, bl, bl + Vec3::X],
+        &[[0.0, 1.0], [1.0, 1.0], [0.0, 0.0], [1.0, 0.0]],
+    );
+}
+
+/// Bottom of the grid at `height`, facing downwards.
+fn create_bottom_cap(mesh_data: &mut MeshData, cells_count: UVec2, height: f32) {
+    let size = cells_count.as_vec2();
+    let bl = Vec3::Z * height;
+    let br = bl + Vec3::X * size.x;
+    let tl = bl + Vec3::Y * size.y;
+    let tr = br + Vec3::Y * size.y;
+    mesh_data.create_quad(
+        &[tr, tl, br, bl],
+        &[[size.x, size.y], [0.0, size.y], [size.x, 0.0], [0.0, 0.0]],
+    );
+}
+
+fn create_cliffs(
+    grid: &HeightGrid,
+    cliffs: &mut impl TriangleSink,
+    base: &mut impl TriangleSink,
+    cell: Coord,
+    settings: &MeshSettings,
+) {
     use super::corner::Corner::*;
     use super::flip::FlipAxis::*;
-    create_cliff(grid, mesh_data, cell, (BottomLeft, BottomRight), Horizontal);
-    create_cliff(grid, mesh_data, cell, (BottomRight, TopRight), Vertical);
-    create_cliff(grid, mesh_data, cell, (TopRight, TopLeft), Horizontal);
-    create_cliff(grid, mesh_data, cell, (TopLeft, BottomLeft), Vertical);
+    let edges = [
+        ((BottomLeft, BottomRight), Horizontal),
+        ((BottomRight, TopRight), Vertical),
+        ((TopRight, TopLeft), Horizontal),
+        ((TopLeft, BottomLeft), Vertical),
+    ];
+    for (corners, axis) in edges {
+        create_cliff(grid, cliffs, base, cell, corners, axis, settings);
+    }
 }
 
+/// Emits the cliff faces below the edge of `cell` between `left` and `right` where the
+/// neighbouring cell is lower. Void neighbours count as if their corners were at the void floor.
+/// On the border of the grid the skirt is emitted into `base` instead, if there is one.
 fn create_cliff(
     grid: &HeightGrid,
-    mesh_data: &mut MeshData,
-    cell: UVec2,
+    cliffs: &mut impl TriangleSink,
+    base: &mut impl TriangleSink,
+    cell: Coord,
     (left, right): (Corner, Corner),
     axis: FlipAxis,
+    settings: &MeshSettings,
 ) {
-    let left_height = grid.get_cell(cell).get_height(left);
-    let right_height = grid.get_cell(cell).get_height(right);
-
-    let left_opp = (cell, left).flip(axis);
-    let right_opp = (cell, right).flip(axis);
-
-    if let (Some((opp_coord, opp_corner_l)), Some((_, opp_corner_r))) = (left_opp, right_opp) {
-        if let Some(opp_cell) = grid.try_get_cell(opp_coord) {
-            let left_opposite_height = opp_cell.get_height(opp_corner_l);
-
-            let right_opposite_height = opp_cell.get_height(opp_corner_r);
-
-            let l_pos = grid.get_position(cell, left);
-            let r_pos = grid.get_position(cell, right);
-            let ol_pos = grid.get_position(opp_coord, opp_corner_l);
-            let or_pos = grid.get_position(opp_coord, opp_corner_r);
-            if left_opposite_height < left_height {
-                mesh_data.create_triangle(
-                    &[l_pos, ol_pos, or_pos],
-                    &[
-                        [0.0, left_height as f32],
-                        [0.0, left_opposite_height as f32],
-                        [1.0, right_opposite_height as f32],
-                    ],
-                );
-            }
-            if right_opposite_height < right_height {
-                mesh_data.create_triangle(
-                    &[l_pos, or_pos, r_pos],
-                    &[
-                        [0.0, left_height as f32],
-                        [1.0, right_height as f32],
-                        [1.0, right_opposite_height as f32],
-                    ],
-                );
-            }
-        }
+    let l_pos = grid.get_position(cell, left);
+    let r_pos = grid.get_position(cell, right);
+
+    let opposite = match ((cell, left).flip(axis), (cell, right).flip(axis)) {
+        (Some((opp_coord, opp_corner_l)), Some((_, opp_corner_r))) => grid
+            .try_get_cell(opp_coord)
+            .map(|opp_cell| (opp_coord, opp_cell, opp_corner_l, opp_corner_r)),
+        _ => None,
+    };
+
+    let (mesh_data, ol_pos, or_pos): (&mut dyn TriangleSink, _, _) =
+        match (opposite, settings.skirt) {
+            (Some((_, opp_cell, ..)), _) if opp_cell.is_void() => (
+                cliffs,
+                l_pos.with_z(settings.void_floor),
+                r_pos.with_z(settings.void_floor),
+            ),
+            (Some((opp_coord, _, opp_corner_l, opp_corner_r)), _) => (
+                cliffs,
+                grid.get_position(opp_coord, opp_corner_l),
+                grid.get_position(opp_coord, opp_corner_r),
+            ),
+            (None, Some(skirt)) => (
+                base,
+                l_pos.with_z(skirt.base_height),
+                r_pos.with_z(skirt.base_height),
+            ),
+            (None, None) => return,
+        };
+
+    if ol_pos.z < l_pos.z {
+        mesh_data.create_triangle(
+            &[l_pos, ol_pos, or_pos],
+            &[[0.0, l_pos.z], [0.0, ol_pos.z], [1.0, or_pos.z]],
+        );
+    }
+    if or_pos.z < r_pos.z {
+        mesh_data.create_triangle(
+            &[l_pos, or_pos, r_pos],
+            &[[0.0, l_pos.z], [1.0, r_pos.z], [1.0, or_pos.z]],
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::height_grid::direction::Direction;
+
+    fn flat(height: u32) -> HeightGrid {
+        HeightGrid::new((2, 2), vec![(height, height, height, height).into(); 4])
+    }
+
+    #[test]
+    fn border_is_open_without_skirt() {
+        let meshes = build(&flat(1), None, &MeshSettings::default());
+
+        assert_eq!(meshes.cliffs.count_vertices(), 0);
+        assert_eq!(meshes.base.count_vertices(), 0);
+    }
+
+    #[test]
+    fn skirt_surrounds_the_grid() {
+        let settings = MeshSettings {
+            skirt: Some(SkirtSettings::default()),
+            ..default()
+        };
+
+        let meshes = build(&flat(1), None, &settings);
+
+        // Two triangles for each of the 8 border edges plus the bottom cap.
+        assert_eq!(meshes.base.count_vertices(), 8 * 2 * 3 + 4);
+        assert_eq!(meshes.cliffs.count_vertices(), 0);
+    }
+
+    #[test]
+    fn skirt_can_use_cliffs_material() {
+        let settings = MeshSettings {
+            skirt: Some(SkirtSettings {
+                bottom_cap: false,
+                material: SkirtMaterial::Cliffs,
+                ..default()
+            }),
+            ..default()
+        };
+
+        let meshes = build(&flat(1), None, &settings);
+
+        assert_eq!(meshes.cliffs.count_vertices(), 8 * 2 * 3);
+        assert_eq!(meshes.base.count_vertices(), 0);
+    }
+
+    #[test]
+    fn stairs_replace_the_ground_with_steps() {
+        let mut grid = HeightGrid::new((2, 1), [(0, 0, 0, 0).into(), (1, 1, 1, 1).into()]);
+        grid.set_feature((0, 0), Some(CellFeature::Stairs(Direction::Right)));
+
+        let meshes = build(&grid, None, &MeshSettings::default());
+
+        // Two treads next to the flat cell, each step with a riser and two side faces.
+        assert_eq!(meshes.ground.count_vertices(), 2 * 6 + 4);
+        assert_eq!(meshes.cliffs.count_vertices(), 2 * (6 + 2 * 3));
+    }
+
+    #[test]
+    fn void_cells_have_walls_down_to_the_floor() {
+        let mut grid = flat(1);
+        grid.set_void((0, 0), true);
+
+        let meshes = build(&grid, None, &MeshSettings::default());
+
+        // Three ground cells and two walls facing the void.
+        assert_eq!(meshes.ground.count_vertices(), 3 * 4);
+        assert_eq!(meshes.cliffs.count_vertices(), 2 * 2 * 3);
     }
 }
@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+
+use super::mesh_data::TriangleSink;
+use crate::height_grid::{coord::Coord, direction::Direction, HeightGrid};
+
+/// Steps per unit of height the stairs climb.
+const STEPS_PER_HEIGHT: f32 = 2.0;
+
+/// A staircase between the low and the high edge of a cell whose feature points in `direction`.
+/// Treads go into `treads`, risers and the side faces between the steps and the ramp the cliffs
+/// are built against go into `risers`.
+pub(super) fn create_stairs(
+    grid: &HeightGrid,
+    treads: &mut impl TriangleSink,
+    risers: &mut impl TriangleSink,
+    cell: Coord,
+    direction: Direction,
+) {
+    let (back_a, back_b) = direction.corners();
+    let (front_a, front_b) = direction.opposite().corners();
+    let [mut fa, mut fb, mut ba, mut bb] =
+        [front_a, front_b, back_a, back_b].map(|corner| grid.get_position(cell, corner));
+
+    // Always climb from the lower to the higher edge.
+    if ba.z + bb.z < fa.z + fb.z {
+        (fa, ba) = (ba, fa);
+        (fb, bb) = (bb, fb);
+    }
+
+    let rise = (ba.z - fa.z).max(bb.z - fb.z);
+    let steps = ((rise * STEPS_PER_HEIGHT).ceil() as u32).max(1);
+    let up = Vec3::Z;
+    let forward = (ba - fa).with_z(0.0);
+    let side_a = (fa - fb).with_z(0.0);
+
+    for step in 0..steps {
+        let t0 = step as f32 / steps as f32;
+        let t1 = (step + 1) as f32 / steps as f32;
+
+        // Points on the ramp below the front and back of the step on both sides.
+        let (a0, a1) = (fa.lerp(ba, t0), fa.lerp(ba, t1));
+        let (b0, b1) = (fb.lerp(bb, t0), fb.lerp(bb, t1));
+        let (ta, tb) = (a0.with_z(a1.z), b0.with_z(b1.z));
+
+        create_facing_quad(
+            treads,
+            [ta, tb, a1, b1],
+            [[0.0, t0], [1.0, t0], [0.0, t1], [1.0, t1]],
+            up,
+        );
+
+        if a1.z > a0.z || b1.z > b0.z {
+            create_facing_quad(
+                risers,
+                [a0, b0, ta, tb],
+                [[0.0, a0.z], [1.0, b0.z], [0.0, ta.z], [1.0, tb.z]],
+                -forward,
+            );
+        }
+        if a1.z > a0.z {
+            create_facing_triangle(
+                risers,
+                [a0, ta, a1],
+                [[t0, a0.z], [t0, ta.z], [t1, a1.z]],
+                side_a,
+            );
+        }
+        if b1.z > b0.z {
+            create_facing_triangle(
+                risers,
+                [b0, tb, b1],
+                [[t0, b0.z], [t0, tb.z], [t1, b1.z]],
+                -side_a,
+            );
+        }
+    }
+}
+
+/// Adds a triangle wound so that its front face points along `facing`.
+fn create_facing_triangle(
+    mesh_data: &mut impl TriangleSink,
+    [a, b, c]: [Vec3; 3],
+    [uv_a, uv_b, uv_c]: [[f32; 2]; 3],
+    facing: Vec3,
+) {
+    if (b - a).cross(c - a).dot(facing) >= 0.0 {
+        mesh_data.create_triangle(&[a, b, c], &[uv_a, uv_b, uv_c]);
+    } else {
+        mesh_data.create_triangle(&[a, c, b], &[uv_a, uv_c, uv_b]);
+    }
+}
+
+/// Adds a quad given as two rows of two vertices, like [`TriangleSink::create_quad`], wound so that
+/// its front face points along `facing`.
+fn create_facing_quad(
+    mesh_data: &mut impl TriangleSink,
+    [a, b, c, d]: [Vec3; 4],
+    [uv_a, uv_b, uv_c, uv_d]: [[f32; 2]; 4],
+    facing: Vec3,
+) {
+    create_facing_triangle(mesh_data, [a, c, b], [uv_a, uv_c, uv_b], facing);
+    create_facing_triangle(mesh_data, [b, c, d], [uv_b, uv_c, uv_d], facing);
+}
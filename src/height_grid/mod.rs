@@ -1,18 +1,50 @@
 pub mod cell;
 pub mod cell_iter;
 mod component;
+pub mod coord;
 pub mod corner;
+pub mod direction;
+pub mod events;
 pub mod flip;
+pub mod line_of_sight;
 pub mod mesh_builder;
+pub mod pathfinding;
+pub mod raycast;
+pub mod region;
+pub mod terrace;
+pub mod theme;
+pub mod vertex;
+pub mod walkability;
+pub mod water;
 
 use bevy::prelude::*;
 pub use component::HeightGrid;
-use mesh_builder::MeshBuilderPlugin;
+use events::{emit_height_grid_changes, HeightGridChanged, TerrainMeshed};
+use mesh_builder::{generate_meshes, MeshBuilderPlugin};
+use theme::apply_terrain_themes;
+pub use theme::{HeightGridBundle, TerrainTheme};
+use walkability::WalkabilityPlugin;
+use water::WaterPlugin;
 
 pub struct HeightGridPlugin;
 
 impl Plugin for HeightGridPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(MeshBuilderPlugin);
+        app.register_type::<HeightGrid>()
+            .register_type::<cell::Cell>()
+            .register_type::<theme::Terrain>()
+            .register_type::<theme::Ground>()
+            .register_type::<theme::Cliffs>()
+            .register_type::<theme::Water>()
+            .register_type::<theme::Base>()
+            .register_type::<TerrainTheme>()
+            .register_type::<water::WaterLevel>()
+            .register_type::<mesh_builder::MeshSettings>()
+            .register_type::<mesh_builder::RequiresMeshing>()
+            .add_event::<HeightGridChanged>()
+            .add_event::<TerrainMeshed>()
+            .add_plugins((MeshBuilderPlugin, WalkabilityPlugin, WaterPlugin))
+            .add_systems(Update, apply_terrain_themes.before(generate_meshes))
+            .add_systems(PostUpdate, emit_height_grid_changes);
     }
 }
@@ -0,0 +1,457 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+
+use bevy::prelude::*;
+
+use super::{
+    coord::Coord,
+    direction::{Direction, DIRECTIONS},
+    HeightGrid,
+};
+
+/// Limits on what terrain a walking unit can cross.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct TraversalConfig {
+    /// Highest discontinuity between two neighbouring cells that can still be stepped over.
+    /// With `0` every cliff blocks movement.
+    pub max_step_height: u32,
+    /// Steepest walkable slope as height gained per unit of horizontal distance.
+    pub max_slope: f32,
+}
+
+impl Default for TraversalConfig {
+    fn default() -> Self {
+        Self {
+            max_step_height: 0,
+            max_slope: 1.0,
+        }
+    }
+}
+
+/// The largest height difference between the corners two neighbouring cells share, i.e. the
+/// height of the cliff the mesh builder emits on that edge.
+pub fn edge_step(grid: &HeightGrid, coord: impl Into<Coord>, direction: Direction) -> Option<u32> {
+    let coord = coord.into();
+    let neighbour = grid.neighbour(coord, direction)?;
+    let (a, b) = direction.corners();
+    let (opp_a, opp_b) = direction.opposite().corners();
+
+    Some(
+        grid.get_height(coord, a)
+            .abs_diff(grid.get_height(neighbour, opp_a))
+            .max(
+                grid.get_height(coord, b)
+                    .abs_diff(grid.get_height(neighbour, opp_b)),
+            ),
+    )
+}
+
+/// Moves from `coord` to its neighbour in `direction`, returning the neighbour and the walked
+/// distance, or `None` if the edge is blocked by a cliff, a too steep slope or a void cell. Ramps
+/// and stairs can be walked along their direction no matter how steep they are.
+pub fn traverse(
+    grid: &HeightGrid,
+    coord: Coord,
+    direction: Direction,
+    config: &TraversalConfig,
+) -> Option<(Coord, f32)> {
+    let neighbour = grid.neighbour(coord, direction)?;
+    if grid.get_cell(coord).is_void() || grid.get_cell(neighbour).is_void() {
+        return None;
+    }
+    if edge_step(grid, coord, direction)? > config.max_step_height {
+        return None;
+    }
+
+    let from = cell_center(grid, coord);
+    let to = cell_center(grid, neighbour);
+    let exit = edge_center(grid, coord, direction);
+    let entry = edge_center(grid, neighbour, direction.opposite());
+
+    let steep = |v: Vec3| v.z.abs() > config.max_slope * v.xy().length();
+    let along_feature = |coord: Coord| {
+        grid.get_cell(coord).feature().is_some_and(|feature| {
+            feature.direction() == direction || feature.direction() == direction.opposite()
+        })
+    };
+    if (steep(exit - from) && !along_feature(coord))
+        || (steep(to - entry) && !along_feature(neighbour))
+    {
+        return None;
+    }
+
+    Some((
+        neighbour,
+        (exit - from).length() + (entry - exit).length() + (to - entry).length(),
+    ))
+}
+
+/// The center of a cell on its surface. Void cells report where their surface would be.
+pub fn cell_center(grid: &HeightGrid, coord: impl Into<Coord>) -> Vec3 {
+    let coord = coord.into();
+    let center = coord.center();
+    center.extend(grid.sample_cell_height(coord, center))
+}
+
+fn edge_center(grid: &HeightGrid, coord: Coord, direction: Direction) -> Vec3 {
+    let (a, b) = direction.corners();
+    (grid.get_position(coord, a) + grid.get_position(coord, b)) / 2.0
+}
+
+#[derive(Debug, PartialEq)]
+struct OpenCell {
+    estimate: f32,
+    coord: Coord,
+}
+
+impl Eq for OpenCell {}
+
+impl Ord for OpenCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .total_cmp(&self.estimate)
+            .then_with(|| self.coord.cmp(&other.coord))
+    }
+}
+
+impl PartialOrd for OpenCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A* search over the cells of the grid. The returned path starts with `start` and ends with
+/// `goal`.
+pub fn find_path(
+    grid: &HeightGrid,
+    start: impl Into<Coord>,
+    goal: impl Into<Coord>,
+    config: &TraversalConfig,
+) -> Option<Vec<Coord>> {
+    let start = start.into();
+    let goal = goal.into();
+    if !grid.valid_coord(start) || !grid.valid_coord(goal) {
+        return None;
+    }
+    if grid.get_cell(start).is_void() || grid.get_cell(goal).is_void() {
+        return None;
+    }
+
+    let heuristic = |coord: Coord| coord.manhattan_distance(goal) as f32;
+
+    let mut open = BinaryHeap::from([OpenCell {
+        estimate: heuristic(start),
+        coord: start,
+    }]);
+    let mut came_from: HashMap<Coord, Coord> = HashMap::new();
+    let mut costs: HashMap<Coord, f32> = HashMap::from([(start, 0.0)]);
+
+    while let Some(OpenCell { estimate, coord }) = open.pop() {
+        if coord == goal {
+            let mut path = vec![goal];
+            let mut current = goal;
+            while let Some(&previous) = came_from.get(&current) {
+                path.push(previous);
+                current = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        let cost = costs[&coord];
+        if estimate > cost + heuristic(coord) {
+            continue;
+        }
+
+        for direction in DIRECTIONS {
+            let Some((neighbour, distance)) = traverse(grid, coord, direction, config) else {
+                continue;
+            };
+            let neighbour_cost = cost + distance;
+            if costs
+                .get(&neighbour)
+                .is_some_and(|&known| known <= neighbour_cost)
+            {
+                continue;
+            }
+            costs.insert(neighbour, neighbour_cost);
+            came_from.insert(neighbour, coord);
+            open.push(OpenCell {
+                estimate: neighbour_cost + heuristic(neighbour),
+                coord: neighbour,
+            });
+        }
+    }
+
+    None
+}
+
+/// Whether the straight line between two cell centers only crosses traversable edges. Lines
+/// passing exactly through a grid vertex need both ways around it to be open.
+pub fn walkable_line(grid: &HeightGrid, from: Coord, to: Coord, config: &TraversalConfig) -> bool {
+    let delta = to.as_ivec2() - from.as_ivec2();
+    let step_x = if delta.x > 0 {
+        Direction::Right
+    } else {
+        Direction::Left
+    };
+    let step_y = if delta.y > 0 {
+        Direction::Top
+    } else {
+        Direction::Bottom
+    };
+    let UVec2 { x: nx, y: ny } = delta.abs().as_uvec2();
+    let step = |coord, direction| traverse(grid, coord, direction, config).map(|(next, _)| next);
+
+    let mut current = from;
+    let (mut ix, mut iy) = (0, 0);
+    while ix < nx || iy < ny {
+        // Parameters of the next vertical and horizontal edge crossings, scaled by 2 * nx * ny.
+        let cross_x = (2 * ix + 1) as u64 * ny as u64;
+        let cross_y = (2 * iy + 1) as u64 * nx as u64;
+
+        let next = if ix < nx && (iy >= ny || cross_x < cross_y) {
+            ix += 1;
+            step(current, step_x)
+        } else if iy < ny && (ix >= nx || cross_y < cross_x) {
+            iy += 1;
+            step(current, step_y)
+        } else {
+            ix += 1;
+            iy += 1;
+            let via_x = step(current, step_x).and_then(|coord| step(coord, step_y));
+            let via_y = step(current, step_y).and_then(|coord| step(coord, step_x));
+            via_x.filter(|_| via_y.is_some())
+        };
+
+        match next {
+            Some(next) => current = next,
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Shortens a cell path by skipping every cell that can be bypassed in a straight walkable line
+/// and turns it into world space points on the terrain surface, with a point wherever the path
+/// enters a new cell.
+pub fn smooth_path(grid: &HeightGrid, path: &[Coord], config: &TraversalConfig) -> Vec<Vec3> {
+    let Some(&first) = path.first() else {
+        return vec![];
+    };
+
+    let mut waypoints = vec![first];
+    let mut anchor = 0;
+    while anchor + 1 < path.len() {
+        let next = (anchor + 2..path.len())
+            .rev()
+            .find(|&candidate| walkable_line(grid, path[anchor], path[candidate], config))
+            .unwrap_or(anchor + 1);
+        waypoints.push(path[next]);
+        anchor = next;
+    }
+
+    let mut points = vec![cell_center(grid, first)];
+    for segment in waypoints.windows(2) {
+        let from = segment[0].as_vec2() + 0.5;
+        let to = segment[1].as_vec2() + 0.5;
+        let delta = to - from;
+
+        let mut crossings: Vec<f32> = [(from.x, to.x, delta.x), (from.y, to.y, delta.y)]
+            .into_iter()
+            .filter(|&(_, _, d)| d != 0.0)
+            .flat_map(|(a, b, d)| {
+                let (min, max) = (a.min(b).ceil() as i32, a.max(b).floor() as i32);
+                (min..=max).map(move |line| (line as f32 - a) / d)
+            })
+            .collect();
+        crossings.sort_by(f32::total_cmp);
+        crossings.dedup();
+
+        points.extend(crossings.into_iter().chain([1.0]).map(|t| {
+            let position = from + delta * t;
+            let height = grid
+                .sample_height(position)
+                .expect("path stays inside the grid");
+            position.extend(height)
+        }));
+    }
+
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::height_grid::cell::CellFeature;
+
+    fn flat(cells_count: (u32, u32)) -> HeightGrid {
+        let count = (cells_count.0 * cells_count.1) as usize;
+        HeightGrid::new(cells_count, vec![(0, 0, 0, 0).into(); count])
+    }
+
+    /// A 3x3 grid with a raised plateau in the center column.
+    fn wall(height: u32) -> HeightGrid {
+        let low = (0, 0, 0, 0).into();
+        let high = (height, height, height, height).into();
+        HeightGrid::new((3, 3), [low, high, low, low, high, low, low, high, low])
+    }
+
+    #[test]
+    fn edge_step_works() {
+        let grid = wall(2);
+
+        assert_eq!(
+            edge_step(&grid, Coord::new(0, 0), Direction::Right),
+            Some(2)
+        );
+        assert_eq!(edge_step(&grid, Coord::new(1, 0), Direction::Top), Some(0));
+        assert_eq!(edge_step(&grid, Coord::new(0, 0), Direction::Left), None);
+    }
+
+    #[test]
+    fn straight_path_on_flat_grid() {
+        let grid = flat((4, 1));
+
+        let path = find_path(&grid, (0, 0), (3, 0), &TraversalConfig::default());
+
+        assert_eq!(
+            path,
+            Some(vec![
+                Coord::new(0, 0),
+                Coord::new(1, 0),
+                Coord::new(2, 0),
+                Coord::new(3, 0)
+            ])
+        );
+    }
+
+    #[test]
+    fn cliffs_block_path() {
+        let grid = wall(1);
+
+        assert_eq!(
+            find_path(&grid, (0, 0), (2, 0), &TraversalConfig::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn max_step_height_allows_small_cliffs() {
+        let grid = wall(1);
+        let config = TraversalConfig {
+            max_step_height: 1,
+            max_slope: 1.0,
+        };
+
+        let path = find_path(&grid, (0, 0), (2, 0), &config).unwrap();
+
+        assert_eq!(path.len(), 3);
+    }
+
+    #[test]
+    fn path_walks_around_obstacle() {
+        let low = (0, 0, 0, 0).into();
+        let high = (3, 3, 3, 3).into();
+        let grid = HeightGrid::new((3, 2), [low, high, low, low, low, low]);
+
+        let path = find_path(&grid, (0, 0), (2, 0), &TraversalConfig::default()).unwrap();
+
+        assert_eq!(
+            path,
+            vec![
+                Coord::new(0, 0),
+                Coord::new(0, 1),
+                Coord::new(1, 1),
+                Coord::new(2, 1),
+                Coord::new(2, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn steep_slopes_block_path() {
+        let grid = HeightGrid::new(
+            (3, 1),
+            [
+                (0, 0, 0, 0).into(),
+                (0, 3, 0, 3).into(),
+                (3, 3, 3, 3).into(),
+            ],
+        );
+
+        assert_eq!(
+            find_path(&grid, (0, 0), (2, 0), &TraversalConfig::default()),
+            None
+        );
+
+        let config = TraversalConfig {
+            max_slope: 3.0,
+            ..default()
+        };
+        assert!(find_path(&grid, (0, 0), (2, 0), &config).is_some());
+    }
+
+    #[test]
+    fn walkable_line_needs_both_ways_around_vertex() {
+        let low = (0, 0, 0, 0).into();
+        let high = (3, 3, 3, 3).into();
+        let grid = HeightGrid::new((2, 2), [low, high, low, low]);
+        let config = TraversalConfig::default();
+
+        assert!(!walkable_line(
+            &grid,
+            Coord::new(0, 0),
+            Coord::new(1, 1),
+            &config
+        ));
+        assert!(walkable_line(
+            &flat((2, 2)),
+            Coord::new(0, 0),
+            Coord::new(1, 1),
+            &config
+        ));
+    }
+
+    #[test]
+    fn smooth_path_cuts_corners() {
+        let grid = flat((3, 3));
+        let config = TraversalConfig::default();
+        let path = find_path(&grid, (0, 0), (2, 2), &config).unwrap();
+
+        let smoothed = smooth_path(&grid, &path, &config);
+
+        assert_eq!(smoothed.first(), Some(&Vec3::new(0.5, 0.5, 0.0)));
+        assert_eq!(smoothed.last(), Some(&Vec3::new(2.5, 2.5, 0.0)));
+        assert!(smoothed
+            .iter()
+            .all(|point| (point.x - point.y).abs() < f32::EPSILON));
+    }
+
+    #[test]
+    fn void_cells_block_path() {
+        let mut grid = flat((3, 1));
+        grid.set_void((1, 0), true);
+
+        let config = TraversalConfig::default();
+        assert_eq!(find_path(&grid, (0, 0), (2, 0), &config), None);
+        assert_eq!(find_path(&grid, (1, 0), (1, 0), &config), None);
+    }
+
+    #[test]
+    fn ramps_connect_plateaus() {
+        let mut grid = wall(2);
+        let config = TraversalConfig::default();
+        assert_eq!(find_path(&grid, (0, 0), (1, 0), &config), None);
+
+        grid.set_feature((0, 0), Some(CellFeature::Ramp(Direction::Right)));
+        assert_eq!(
+            find_path(&grid, (0, 0), (1, 0), &config),
+            Some(vec![Coord::new(0, 0), Coord::new(1, 0)])
+        );
+        assert!(traverse(&grid, Coord::new(0, 0), Direction::Top, &config).is_none());
+    }
+}
@@ -0,0 +1,247 @@
+use bevy::prelude::*;
+
+use super::{
+    coord::Coord,
+    corner::Corner,
+    line_of_sight::clip_to_grid,
+    mesh_builder::{build_cell, MeshSettings, TriangleSink},
+    theme::Surface,
+    HeightGrid,
+};
+
+/// Where a ray hits the terrain of a grid, in the space of the grid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridHit {
+    /// Distance along the ray in multiples of its direction.
+    pub distance: f32,
+    pub position: Vec3,
+    /// Normal of the hit triangle, facing the origin of the ray.
+    pub normal: Vec3,
+    pub surface: Surface,
+    /// The cell the hit triangle belongs to. Cliffs belong to the higher of the two cells, which
+    /// is the one behind the cliff when looking at it.
+    pub cell: Coord,
+    /// The corner of [`GridHit::cell`] closest to the hit.
+    pub corner: Corner,
+}
+
+/// How far outside of a triangle a ray may pass and still hit it, so that rays do not slip
+/// through the seams between triangles.
+const TOLERANCE: f32 = 1e-5;
+
+/// The first point where the ray `origin + t * direction` with `t >= 0` hits the triangles the
+/// mesh builder emits for the grid, without waiting for a mesh or collider to be built.
+///
+/// The ray walks the cells it passes over in order and only tests the triangles of those. All
+/// triangles of a cell, including its cliffs, lie within the column above the cell, so the first
+/// cell with a hit contains the closest one. The bottom cap of the skirt is never hit.
+pub fn cast_ray(
+    grid: &HeightGrid,
+    settings: &MeshSettings,
+    origin: Vec3,
+    direction: Vec3,
+) -> Option<GridHit> {
+    if grid.cells_count.cmpeq(UVec2::ZERO).any() {
+        return None;
+    }
+    let start = origin.xy();
+    let delta = direction.xy();
+    let (t_enter, t_exit) = clip_to_grid(grid, start, delta, f32::INFINITY)?;
+
+    let last = grid.cells_count.as_ivec2() - IVec2::ONE;
+    let mut cell = (start + delta * t_enter)
+        .floor()
+        .as_ivec2()
+        .clamp(IVec2::ZERO, last);
+    let step = IVec2::new(delta.x.signum() as i32, delta.y.signum() as i32);
+    let t_delta = delta.recip().abs();
+    // The distance at which the ray crosses the next cell border on each axis.
+    let border = |axis: usize| {
+        if delta[axis] == 0.0 {
+            return f32::INFINITY;
+        }
+        let next = cell[axis] + i32::from(delta[axis] > 0.0);
+        (next as f32 - start[axis]) / delta[axis]
+    };
+    let mut t_max = Vec2::new(border(0), border(1));
+
+    loop {
+        let coord = Coord::from(cell.as_uvec2());
+        if let Some(hit) = hit_cell(grid, settings, coord, origin, direction) {
+            return Some(hit);
+        }
+
+        let axis = if t_max.x < t_max.y { 0 } else { 1 };
+        if t_max[axis] > t_exit {
+            return None;
+        }
+        cell[axis] += step[axis];
+        t_max[axis] += t_delta[axis];
+        if cell[axis] < 0 || cell[axis] > last[axis] {
+            return None;
+        }
+    }
+}
+
+/// The closest of the triangles it is fed that the ray hits.
+#[derive(Clone, Copy)]
+struct ClosestTriangle {
+    origin: Vec3,
+    direction: Vec3,
+    closest: Option<(f32, [Vec3; 3])>,
+}
+
+impl TriangleSink for ClosestTriangle {
+    fn create_triangle(&mut self, vertices: &[Vec3; 3], _uvs: &[[f32; 2]; 3]) {
+        let Some(distance) = intersect_triangle(self.origin, self.direction, *vertices) else {
+            return;
+        };
+        if !self.closest.is_some_and(|(closest, _)| closest <= distance) {
+            self.closest = Some((distance, *vertices));
+        }
+    }
+}
+
+/// The closest hit of the ray with the ground and cliffs of `cell`.
+fn hit_cell(
+    grid: &HeightGrid,
+    settings: &MeshSettings,
+    cell: Coord,
+    origin: Vec3,
+    direction: Vec3,
+) -> Option<GridHit> {
+    let ray = ClosestTriangle {
+        origin,
+        direction,
+        closest: None,
+    };
+    let (mut ground, mut cliffs, mut base) = (ray, ray, ray);
+    build_cell(grid, cell, settings, &mut ground, &mut cliffs, &mut base);
+
+    // Ground comes first, so that it wins over the cliffs hanging from its edges.
+    [
+        (ground, Surface::Ground),
+        (cliffs, Surface::Cliff),
+        (base, Surface::Cliff),
+    ]
+    .into_iter()
+    .filter_map(|(hit, surface)| Some((hit.closest?, surface)))
+    .min_by(|((a, _), _), ((b, _), _)| a.total_cmp(b))
+    .map(|((distance, [a, b, c]), surface)| {
+        let position = origin + direction * distance;
+        let normal = (b - a).cross(c - a).normalize_or_zero();
+        GridHit {
+            distance,
+            position,
+            normal: if normal.dot(direction) > 0.0 {
+                -normal
+            } else {
+                normal
+            },
+            surface,
+            cell,
+            corner: Corner::nearest(position.xy() - cell.as_vec2()),
+        }
+    })
+}
+
+/// Distance along the ray to where it hits the triangle from either side.
+fn intersect_triangle(origin: Vec3, direction: Vec3, [a, b, c]: [Vec3; 3]) -> Option<f32> {
+    let (ab, ac) = (b - a, c - a);
+    let p = direction.cross(ac);
+    let determinant = ab.dot(p);
+    if determinant.abs() < 1e-12 {
+        return None;
+    }
+
+    let to_origin = origin - a;
+    let u = to_origin.dot(p) / determinant;
+    let q = to_origin.cross(ab);
+    let v = direction.dot(q) / determinant;
+    if u < -TOLERANCE || v < -TOLERANCE || u + v > 1.0 + TOLERANCE {
+        return None;
+    }
+
+    let distance = ac.dot(q) / determinant;
+    (distance >= 0.0).then_some(distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::height_grid::cell::Diagonal;
+
+    fn row(heights: &[u32]) -> HeightGrid {
+        let cells: Vec<_> = heights.iter().map(|&h| (h, h, h, h).into()).collect();
+        HeightGrid::new((heights.len() as u32, 1), cells)
+    }
+
+    fn down(grid: &HeightGrid, x: f32, y: f32) -> Option<GridHit> {
+        cast_ray(
+            grid,
+            &MeshSettings::default(),
+            Vec3::new(x, y, 10.0),
+            Vec3::NEG_Z,
+        )
+    }
+
+    #[test]
+    fn ray_hits_the_ground_from_above() {
+        let grid = row(&[1, 2]);
+        let hit = down(&grid, 1.75, 0.25).unwrap();
+
+        assert_eq!(hit.surface, Surface::Ground);
+        assert_eq!(hit.cell, Coord::new(1, 0));
+        assert_eq!(hit.corner, Corner::BottomRight);
+        assert_eq!(hit.position, Vec3::new(1.75, 0.25, 2.0));
+        assert_eq!(hit.normal, Vec3::Z);
+        assert_eq!(hit.distance, 8.0);
+        assert_eq!(down(&grid, 2.5, 0.5), None);
+    }
+
+    #[test]
+    fn ray_hits_the_cliff_of_the_higher_cell() {
+        let grid = row(&[0, 0, 3]);
+        let hit = cast_ray(
+            &grid,
+            &MeshSettings::default(),
+            Vec3::new(-1.0, 0.5, 1.0),
+            Vec3::X,
+        )
+        .unwrap();
+
+        assert_eq!(hit.surface, Surface::Cliff);
+        assert_eq!(hit.cell, Coord::new(2, 0));
+        assert!((hit.position - Vec3::new(2.0, 0.5, 1.0)).length() < 1e-5);
+        assert!((hit.normal - Vec3::NEG_X).length() < 1e-5);
+    }
+
+    #[test]
+    fn ray_honours_the_diagonal() {
+        // A saddle where the automatic split runs from the bottom left to the top right.
+        let mut grid = HeightGrid::new((1, 1), [(0, 2, 2, 0).into()]);
+        assert_eq!(down(&grid, 0.5, 0.5).unwrap().position.z, 2.0);
+
+        grid.set_diagonal((0, 0), Some(Diagonal::Backslash));
+        assert_eq!(down(&grid, 0.5, 0.5).unwrap().position.z, 0.0);
+    }
+
+    #[test]
+    fn ray_passes_through_void_cells() {
+        let mut grid = row(&[1, 1, 1]);
+        grid.set_void((1, 0), true);
+
+        assert_eq!(down(&grid, 1.5, 0.5), None);
+
+        // Looking into the void at an angle hits the wall of the cell behind it.
+        let hit = cast_ray(
+            &grid,
+            &MeshSettings::default(),
+            Vec3::new(1.0, 0.5, 2.0),
+            Vec3::new(1.0, 0.0, -2.0),
+        )
+        .unwrap();
+        assert_eq!(hit.surface, Surface::Cliff);
+        assert_eq!(hit.cell, Coord::new(2, 0));
+    }
+}
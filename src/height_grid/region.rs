@@ -0,0 +1,237 @@
+use std::collections::BTreeSet;
+
+use bevy::prelude::*;
+
+use super::{
+    cell_iter::{inside_circle, CellRect, Spiral},
+    coord::Coord,
+    direction::{Direction, DIRECTIONS},
+};
+
+/// An arbitrary set of cells, iterated row by row like the cells of a grid. Circles and rings are
+/// built below a limit, e.g. the size of a grid, so that a large radius costs no more than the
+/// grid has cells.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CellRegion(BTreeSet<Coord>);
+
+impl CellRegion {
+    /// The cells below `limit` whose centers are at most `radius` away from the center of
+    /// `center`.
+    pub fn circle(center: impl Into<Coord>, radius: u32, limit: impl Into<UVec2>) -> Self {
+        let center = center.into();
+        let in_range = inside_circle(center, radius);
+        CellRect::from_center(center, UVec2::splat(radius))
+            .clip(limit)
+            .into_iter()
+            .filter(|&coord| in_range(coord))
+            .collect()
+    }
+
+    /// The cells of the circle with radius `outer` that are not part of the circle with radius
+    /// `inner`, both below `limit`.
+    pub fn ring(center: impl Into<Coord>, inner: u32, outer: u32, limit: impl Into<UVec2>) -> Self {
+        let (center, limit) = (center.into(), limit.into());
+        if inner >= outer {
+            return Self::default();
+        }
+        Self::circle(center, outer, limit).difference(&Self::circle(center, inner, limit))
+    }
+
+    /// The cells of the Bresenham line from `from` to `to`, including both ends.
+    pub fn line(from: impl Into<Coord>, to: impl Into<Coord>) -> Self {
+        let (from, to) = (from.into().as_ivec2(), to.into().as_ivec2());
+        let delta = (to - from).abs() * IVec2::new(1, -1);
+        let step = (to - from).signum();
+
+        let mut current = from;
+        let mut error = delta.x + delta.y;
+        let mut cells = Self::default();
+        loop {
+            cells.insert(Coord::from(current.as_uvec2()));
+            if current == to {
+                return cells;
+            }
+            let doubled = 2 * error;
+            if doubled >= delta.y {
+                error += delta.y;
+                current.x += step.x;
+            }
+            if doubled <= delta.x {
+                error += delta.x;
+                current.y += step.y;
+            }
+        }
+    }
+
+    /// The cells reachable from `seed` by stepping over the edges `passes` lets through.
+    /// `passes` is called with a cell of the region and the direction of its neighbour.
+    pub fn flood(seed: impl Into<Coord>, mut passes: impl FnMut(Coord, Direction) -> bool) -> Self {
+        let seed = seed.into();
+        let mut cells = Self::default();
+        cells.insert(seed);
+        let mut stack = vec![seed];
+
+        while let Some(coord) = stack.pop() {
+            for direction in DIRECTIONS {
+                let Some(neighbour) = coord.step(direction) else {
+                    continue;
+                };
+                if !cells.contains(neighbour) && passes(coord, direction) {
+                    cells.insert(neighbour);
+                    stack.push(neighbour);
+                }
+            }
+        }
+
+        cells
+    }
+
+    /// Adds `coord` to the region, returns whether it was not part of it yet.
+    pub fn insert(&mut self, coord: impl Into<Coord>) -> bool {
+        self.0.insert(coord.into())
+    }
+
+    pub fn contains(&self, coord: impl Into<Coord>) -> bool {
+        self.0.contains(&coord.into())
+    }
+
+    /// The smallest rect containing every cell, `None` for an empty region.
+    pub fn bounds(&self) -> Option<CellRect> {
+        let first = *self.0.first()?;
+        Some(self.iter().fold(CellRect::single(first), |bounds, coord| {
+            bounds.union(&CellRect::single(coord))
+        }))
+    }
+
+    pub fn union(&self, other: &CellRegion) -> Self {
+        Self(&self.0 | &other.0)
+    }
+
+    pub fn intersection(&self, other: &CellRegion) -> Self {
+        Self(&self.0 & &other.0)
+    }
+
+    pub fn difference(&self, other: &CellRegion) -> Self {
+        Self(&self.0 - &other.0)
+    }
+
+    /// The cells row by row.
+    pub fn iter(&self) -> impl Iterator<Item = Coord> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// The cells ordered by their distance to `center`, see [`Spiral`].
+    pub fn spiral(&self, center: impl Into<Coord>) -> impl Iterator<Item = Coord> + '_ {
+        let center = center.into();
+        let radius = self
+            .iter()
+            .map(|coord| coord.chebyshev_distance(center))
+            .max()
+            .unwrap_or_default();
+        Spiral::new(center, radius).filter(|&coord| self.contains(coord))
+    }
+}
+
+impl FromIterator<Coord> for CellRegion {
+    fn from_iter<T: IntoIterator<Item = Coord>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for CellRegion {
+    type Item = Coord;
+
+    type IntoIter = std::collections::btree_set::IntoIter<Coord>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl From<CellRect> for CellRegion {
+    fn from(value: CellRect) -> Self {
+        value.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coords(coords: &[(u32, u32)]) -> Vec<Coord> {
+        coords.iter().copied().map(Coord::from).collect()
+    }
+
+    #[test]
+    fn circle_and_ring_work() {
+        let circle = CellRegion::circle((1, 1), 1, UVec2::MAX);
+        assert_eq!(
+            circle.iter().collect::<Vec<_>>(),
+            coords(&[(1, 0), (0, 1), (1, 1), (2, 1), (1, 2)])
+        );
+
+        let ring = CellRegion::ring((1, 1), 0, 1, UVec2::MAX);
+        assert_eq!(ring.iter().count(), 4);
+        assert!(!ring.contains((1, 1)));
+        assert_eq!(
+            CellRegion::ring((1, 1), 1, 1, UVec2::MAX),
+            CellRegion::default()
+        );
+    }
+
+    #[test]
+    fn line_connects_both_ends() {
+        let line = CellRegion::line((0, 0), (4, 2));
+        assert_eq!(
+            line.iter().collect::<Vec<_>>(),
+            coords(&[(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)])
+        );
+        assert_eq!(CellRegion::line((3, 1), (0, 1)).iter().count(), 4);
+        assert_eq!(CellRegion::line((2, 2), (2, 2)).iter().count(), 1);
+    }
+
+    #[test]
+    fn flood_stops_where_edges_do_not_pass() {
+        // A wall between column 1 and 2 of a 4 by 2 area.
+        let flooded = CellRegion::flood((0, 0), |coord, direction| {
+            let next = coord.step(direction).unwrap();
+            next.x() < 4 && next.y() < 2 && !(coord.x() == 1 && direction == Direction::Right)
+        });
+        assert_eq!(flooded, CellRect::new((0, 0), (2, 2)).into());
+    }
+
+    #[test]
+    fn set_operations_work() {
+        let a: CellRegion = CellRect::new((0, 0), (2, 1)).into();
+        let b: CellRegion = CellRect::new((1, 0), (3, 1)).into();
+
+        assert_eq!(a.union(&b), CellRect::new((0, 0), (3, 1)).into());
+        assert_eq!(a.intersection(&b), CellRect::single((1, 0)).into());
+        assert_eq!(a.difference(&b), CellRect::single((0, 0)).into());
+        assert_eq!(a.union(&b).bounds(), Some(CellRect::new((0, 0), (3, 1))));
+        assert_eq!(CellRegion::default().bounds(), None);
+    }
+
+    #[test]
+    fn circle_keeps_cells_below_limit() {
+        let clipped = CellRegion::circle((0, 0), 2, (2, 1));
+        assert_eq!(
+            clipped.iter().collect::<Vec<_>>(),
+            coords(&[(0, 0), (1, 0)])
+        );
+        assert_eq!(CellRegion::circle((0, 0), u32::MAX, (2, 1)), clipped);
+    }
+
+    #[test]
+    fn spiral_starts_at_center() {
+        let circle = CellRegion::circle((2, 2), 2, UVec2::MAX);
+        let spiral: Vec<_> = circle.spiral((2, 2)).collect();
+
+        assert_eq!(spiral.len(), circle.iter().count());
+        assert_eq!(spiral[0], Coord::new(2, 2));
+        assert!(spiral
+            .windows(2)
+            .all(|pair| pair[0].chebyshev_distance(Coord::new(2, 2))
+                <= pair[1].chebyshev_distance(Coord::new(2, 2))));
+    }
+}
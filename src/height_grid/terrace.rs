@@ -0,0 +1,446 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+
+use super::{
+    cell::Cell,
+    coord::Coord,
+    corner::{Corner, CORNERS},
+    direction::{Direction, DIRECTIONS},
+    HeightGrid,
+};
+
+/// Settings for turning a smooth heightfield into flat plateaus separated by cliffs.
+#[derive(Resource, Debug, Clone)]
+pub struct TerraceConfig {
+    /// Heights the plateaus snap to. Samples below the first height end up on the first terrace.
+    pub terrace_heights: Vec<u32>,
+    /// Steps between neighbouring plateaus up to this height become slopes instead of cliffs.
+    /// Ramps over higher cliffs climb at most this much per cell.
+    pub max_slope_step: u32,
+    /// Fraction of the cliff edges between two plateaus that get a ramp. Every plateau gets at
+    /// least one ramp regardless, so that it stays reachable.
+    pub ramp_density: f32,
+}
+
+impl Default for TerraceConfig {
+    fn default() -> Self {
+        Self {
+            terrace_heights: vec![0, 2, 4, 6],
+            max_slope_step: 1,
+            ramp_density: 0.1,
+        }
+    }
+}
+
+impl TerraceConfig {
+    /// The highest terrace height at or below `sample`, or the lowest one if there is none.
+    /// The heights may be in any order.
+    pub fn terrace_height(&self, sample: f32) -> u32 {
+        let heights = self.terrace_heights.iter().copied();
+        heights
+            .clone()
+            .filter(|&height| height as f32 <= sample)
+            .max()
+            .or_else(|| heights.min())
+            .unwrap_or_default()
+    }
+}
+
+/// A boundary between two neighbouring cells of different height, seen from the lower one.
+#[derive(Debug, Clone, Copy)]
+struct Step {
+    low: Coord,
+    high: Coord,
+    direction: Direction,
+    rise: u32,
+}
+
+/// Builds a terraced grid by sampling `heightfield` at every cell center.
+pub fn terrace(
+    cells_count: impl Into<UVec2>,
+    config: &TerraceConfig,
+    heightfield: impl Fn(Vec2) -> f32,
+) -> HeightGrid {
+    let cells_count = cells_count.into();
+    let cells: Vec<Cell> = (0..cells_count.y)
+        .flat_map(|y| (0..cells_count.x).map(move |x| Coord::new(x, y)))
+        .map(|coord| {
+            let height = config.terrace_height(heightfield(coord.as_vec2() + 0.5));
+            (height, height, height, height).into()
+        })
+        .collect();
+    let mut grid = HeightGrid::new(cells_count, cells);
+
+    let plateaus = label_plateaus(&grid);
+    let mut connections = UnionFind::new(plateaus.count);
+    let mut cliffs: BTreeMap<(usize, usize), Vec<Step>> = BTreeMap::new();
+
+    for step in find_steps(&grid) {
+        let low = plateaus.label(&grid, step.low);
+        let high = plateaus.label(&grid, step.high);
+
+        if step.rise <= config.max_slope_step {
+            raise_edge(&mut grid, step);
+            connections.union(low, high);
+        } else {
+            cliffs
+                .entry((low.min(high), low.max(high)))
+                .or_default()
+                .push(step);
+        }
+    }
+
+    let mut unconnected = vec![];
+    for ((a, b), steps) in cliffs {
+        let ramps = (steps.len() as f32 * config.ramp_density.clamp(0.0, 1.0)).round() as usize;
+        if ramps == 0 {
+            unconnected.push(((a, b), steps));
+            continue;
+        }
+        if place_ramps(&mut grid, &steps, ramps, config.max_slope_step) > 0 {
+            connections.union(a, b);
+        } else {
+            unconnected.push(((a, b), steps));
+        }
+    }
+
+    for ((a, b), steps) in unconnected {
+        if connections.find(a) != connections.find(b)
+            && place_ramps(&mut grid, &steps, 1, config.max_slope_step) > 0
+        {
+            connections.union(a, b);
+        }
+    }
+
+    grid
+}
+
+fn plateau_height(grid: &HeightGrid, coord: Coord) -> u32 {
+    grid.get_cell(coord).get_height(Corner::BottomLeft)
+}
+
+/// Every boundary between cells of different height, each reported once.
+fn find_steps(grid: &HeightGrid) -> Vec<Step> {
+    let mut steps = vec![];
+    for y in 0..grid.cells_count.y {
+        for x in 0..grid.cells_count.x {
+            let coord = Coord::new(x, y);
+            for direction in [Direction::Top, Direction::Right] {
+                let Some(neighbour) = grid.neighbour(coord, direction) else {
+                    continue;
+                };
+                let height = plateau_height(grid, coord);
+                let neighbour_height = plateau_height(grid, neighbour);
+
+                use std::cmp::Ordering::*;
+                match height.cmp(&neighbour_height) {
+                    Less => steps.push(Step {
+                        low: coord,
+                        high: neighbour,
+                        direction,
+                        rise: neighbour_height - height,
+                    }),
+                    Equal => {}
+                    Greater => steps.push(Step {
+                        low: neighbour,
+                        high: coord,
+                        direction: direction.opposite(),
+                        rise: height - neighbour_height,
+                    }),
+                }
+            }
+        }
+    }
+    steps
+}
+
+/// Spreads up to `count` ramps evenly over the steps of a single plateau boundary that have
+/// room for one, returning how many were placed.
+fn place_ramps(grid: &mut HeightGrid, steps: &[Step], count: usize, max_rise: u32) -> usize {
+    let fitting: Vec<Step> = steps
+        .iter()
+        .copied()
+        .filter(|&step| ramp_cells(grid, step, max_rise).is_some())
+        .collect();
+    let count = count.min(fitting.len());
+
+    let mut placed = 0;
+    for i in 0..count {
+        let step = fitting[(2 * i + 1) * fitting.len() / (2 * count)];
+        // Ramps placed before may have taken the cells of this one.
+        if let Some(cells) = ramp_cells(grid, step, max_rise) {
+            build_ramp(grid, step, &cells);
+            placed += 1;
+        }
+    }
+    placed
+}
+
+/// The cells a ramp up `step` runs through, starting with the lower cell and leading away from
+/// the cliff, so that it climbs at most `max_rise` per cell. `None` if the ramp and the cell it
+/// is entered from would leave the grid or cover cells that are not flat on the lower plateau.
+fn ramp_cells(grid: &HeightGrid, step: Step, max_rise: u32) -> Option<Vec<Coord>> {
+    let bottom = plateau_height(grid, step.low);
+    let (high_a, high_b) = step.direction.opposite().corners();
+    let top = grid
+        .get_height(step.high, high_a)
+        .max(grid.get_height(step.high, high_b));
+    let length = (top - bottom).div_ceil(max_rise.max(1)) as usize;
+
+    // The sides of a ramp are cliffs, so it needs a landing at its foot.
+    let mut cells = vec![step.low];
+    while cells.len() < length + 1 {
+        let next = grid.neighbour(*cells.last()?, step.direction.opposite())?;
+        cells.push(next);
+    }
+
+    let flat = |coord: Coord| {
+        CORNERS
+            .iter()
+            .all(|&corner| grid.get_height(coord, corner) == bottom)
+    };
+    if !cells.iter().all(|&coord| flat(coord)) {
+        return None;
+    }
+    cells.pop();
+    Some(cells)
+}
+
+/// Turns `cells` into a ramp that climbs evenly from the lower plateau to the edge of the
+/// higher cell of `step`.
+fn build_ramp(grid: &mut HeightGrid, step: Step, cells: &[Coord]) {
+    let bottom = plateau_height(grid, step.low);
+    let (near_a, near_b) = step.direction.corners();
+    let (far_a, far_b) = step.direction.opposite().corners();
+    let length = cells.len() as u32;
+    // Height of the ramp `distance` cell edges away from the cliff, below a corner at `top`.
+    let edge = |top: u32, distance: u32| top - (top - bottom) * distance / length;
+
+    // The far corners of the lower cell line up with the near corners of the higher one.
+    let tops = [far_a, far_b].map(|corner| grid.get_height(step.high, corner));
+    for (distance, &coord) in (0..).zip(cells) {
+        let mut cell = *grid.get_cell(coord);
+        for ((near, far), top) in [(near_a, far_a), (near_b, far_b)].into_iter().zip(tops) {
+            cell.set_height(near, edge(top, distance));
+            cell.set_height(far, edge(top, distance + 1));
+        }
+        grid.set_cell(coord, cell);
+    }
+}
+
+/// Lifts the corners of the lower cell on the shared edge up to the higher cell, turning the
+/// lower cell into a slope.
+fn raise_edge(
+    grid: &mut HeightGrid,
+    Step {
+        low,
+        high,
+        direction,
+        ..
+    }: Step,
+) {
+    let (low_a, low_b) = direction.corners();
+    let (high_a, high_b) = direction.opposite().corners();
+    let high_cell = *grid.get_cell(high);
+    let mut low_cell = *grid.get_cell(low);
+
+    for (low_corner, high_corner) in [(low_a, high_a), (low_b, high_b)] {
+        let height = low_cell
+            .get_height(low_corner)
+            .max(high_cell.get_height(high_corner));
+        low_cell.set_height(low_corner, height);
+    }
+    grid.set_cell(low, low_cell);
+}
+
+struct Plateaus {
+    labels: Vec<usize>,
+    count: usize,
+}
+
+impl Plateaus {
+    fn label(&self, grid: &HeightGrid, coord: Coord) -> usize {
+        self.labels[grid.get_cell_index(coord)]
+    }
+}
+
+/// Labels the 4-connected areas of equal height.
+fn label_plateaus(grid: &HeightGrid) -> Plateaus {
+    let mut labels = vec![usize::MAX; grid.cells.len()];
+    let mut count = 0;
+
+    for y in 0..grid.cells_count.y {
+        for x in 0..grid.cells_count.x {
+            let start = Coord::new(x, y);
+            if labels[grid.get_cell_index(start)] != usize::MAX {
+                continue;
+            }
+
+            let height = plateau_height(grid, start);
+            let mut stack = vec![start];
+            labels[grid.get_cell_index(start)] = count;
+            while let Some(coord) = stack.pop() {
+                for direction in DIRECTIONS {
+                    let Some(neighbour) = grid.neighbour(coord, direction) else {
+                        continue;
+                    };
+                    let index = grid.get_cell_index(neighbour);
+                    if labels[index] == usize::MAX && plateau_height(grid, neighbour) == height {
+                        labels[index] = count;
+                        stack.push(neighbour);
+                    }
+                }
+            }
+            count += 1;
+        }
+    }
+
+    Plateaus { labels, count }
+}
+
+struct UnionFind {
+    parents: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(count: usize) -> Self {
+        Self {
+            parents: (0..count).collect(),
+        }
+    }
+
+    fn find(&mut self, element: usize) -> usize {
+        let parent = self.parents[element];
+        if parent == element {
+            return element;
+        }
+        let root = self.find(parent);
+        self.parents[element] = root;
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let a = self.find(a);
+        let b = self.find(b);
+        self.parents[a] = b;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::height_grid::pathfinding::{find_path, TraversalConfig};
+
+    fn config(ramp_density: f32) -> TerraceConfig {
+        TerraceConfig {
+            terrace_heights: vec![0, 3],
+            max_slope_step: 1,
+            ramp_density,
+        }
+    }
+
+    fn corners(grid: &HeightGrid, coord: (u32, u32)) -> [u32; 4] {
+        let cell = grid.get_cell(coord);
+        [
+            cell.get_height(Corner::TopLeft),
+            cell.get_height(Corner::TopRight),
+            cell.get_height(Corner::BottomLeft),
+            cell.get_height(Corner::BottomRight),
+        ]
+    }
+
+    #[test]
+    fn terrace_height_snaps_down() {
+        let config = config(0.0);
+
+        assert_eq!(config.terrace_height(-1.0), 0);
+        assert_eq!(config.terrace_height(2.9), 0);
+        assert_eq!(config.terrace_height(3.0), 3);
+        assert_eq!(config.terrace_height(10.0), 3);
+    }
+
+    #[test]
+    fn terrace_height_ignores_the_order_of_heights() {
+        let config = TerraceConfig {
+            terrace_heights: vec![3, 0],
+            ..config(0.0)
+        };
+
+        assert_eq!(config.terrace_height(-1.0), 0);
+        assert_eq!(config.terrace_height(2.9), 0);
+        assert_eq!(config.terrace_height(3.0), 3);
+    }
+
+    #[test]
+    fn flat_field_stays_flat() {
+        let grid = terrace((3, 3), &config(1.0), |_| 1.0);
+
+        for cell in grid.cells.iter() {
+            assert_eq!(*cell, (0, 0, 0, 0).into());
+        }
+    }
+
+    #[test]
+    fn small_steps_become_slopes() {
+        let config = TerraceConfig {
+            terrace_heights: vec![0, 1],
+            ..config(0.0)
+        };
+        let grid = terrace((2, 1), &config, |pos| if pos.x > 1.0 { 1.0 } else { 0.0 });
+
+        assert_eq!(corners(&grid, (0, 0)), [0, 1, 0, 1]);
+        assert_eq!(corners(&grid, (1, 0)), [1, 1, 1, 1]);
+    }
+
+    /// Low on the four left columns, high on the rightmost one.
+    fn cliff(pos: Vec2) -> f32 {
+        if pos.x > 4.0 {
+            3.0
+        } else {
+            0.0
+        }
+    }
+
+    #[test]
+    fn plateau_gets_a_single_ramp_without_density() {
+        let grid = terrace((5, 3), &config(0.0), cliff);
+
+        assert_eq!(corners(&grid, (3, 0)), [0, 0, 0, 0]);
+        assert_eq!(corners(&grid, (0, 1)), [0, 0, 0, 0]);
+        assert_eq!(corners(&grid, (1, 1)), [0, 1, 0, 1]);
+        assert_eq!(corners(&grid, (2, 1)), [1, 2, 1, 2]);
+        assert_eq!(corners(&grid, (3, 1)), [2, 3, 2, 3]);
+        assert_eq!(corners(&grid, (3, 2)), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn full_density_ramps_every_edge() {
+        let grid = terrace((5, 3), &config(1.0), cliff);
+
+        for y in 0..3 {
+            assert_eq!(corners(&grid, (3, y)), [2, 3, 2, 3]);
+        }
+    }
+
+    #[test]
+    fn ramps_need_room_for_a_landing() {
+        let grid = terrace(
+            (4, 3),
+            &config(1.0),
+            |pos| if pos.x > 3.0 { 3.0 } else { 0.0 },
+        );
+
+        for y in 0..3 {
+            assert_eq!(corners(&grid, (2, y)), [0, 0, 0, 0]);
+        }
+    }
+
+    #[test]
+    fn terraced_plateaus_can_be_walked_between() {
+        let grid = terrace((5, 3), &config(0.0), cliff);
+
+        let path = find_path(&grid, (0, 0), (4, 0), &TraversalConfig::default());
+        assert!(path.is_some());
+    }
+}
@@ -0,0 +1,284 @@
+use avian3d::prelude::{Collider, ColliderConstructor, RigidBody};
+use bevy::{
+    pbr::wireframe::{Wireframe, WireframeColor},
+    prelude::*,
+};
+
+use super::{
+    mesh_builder::{MeshSettings, RequiresMeshing},
+    water::WaterLevel,
+    HeightGrid,
+};
+
+/// Marks the children of a [`HeightGrid`] that units can stand on and the cursor can hit.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct Terrain;
+
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct Ground;
+
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct Cliffs;
+
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct Water;
+
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct Base;
+
+/// The part of the terrain a ray hit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Surface {
+    Ground,
+    /// The cliffs between cells, the walls along void cells and the skirt around the grid.
+    Cliff,
+}
+
+/// How the children of a [`HeightGrid`] look and collide. The children are created when the
+/// theme is added to a grid and updated whenever it changes.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct TerrainTheme {
+    pub ground: Handle<StandardMaterial>,
+    pub cliffs: Handle<StandardMaterial>,
+    pub water: Handle<StandardMaterial>,
+    pub base: Handle<StandardMaterial>,
+    /// Draws a wireframe of the ground in this colour.
+    pub ground_wireframe: Option<Color>,
+    /// Draws a wireframe of the cliffs in this colour.
+    pub cliffs_wireframe: Option<Color>,
+    /// How colliders are built for the solid parts of the terrain, `None` for no colliders.
+    pub colliders: Option<ColliderConstructor>,
+}
+
+/// Everything needed to spawn a [`HeightGrid`] that meshes itself.
+#[derive(Bundle)]
+pub struct HeightGridBundle {
+    pub height_grid: HeightGrid,
+    pub theme: TerrainTheme,
+    pub water_level: WaterLevel,
+    pub mesh_settings: MeshSettings,
+    pub requires_meshing: RequiresMeshing,
+    pub spatial: SpatialBundle,
+    pub name: Name,
+}
+
+impl HeightGridBundle {
+    pub fn new(height_grid: HeightGrid, theme: TerrainTheme) -> Self {
+        Self {
+            height_grid,
+            theme,
+            water_level: default(),
+            mesh_settings: default(),
+            requires_meshing: RequiresMeshing,
+            spatial: default(),
+            name: Name::new("Height Grid"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Part {
+    Ground,
+    Cliffs,
+    Water,
+    Base,
+}
+
+const PARTS: [Part; 4] = [Part::Ground, Part::Cliffs, Part::Water, Part::Base];
+
+impl Part {
+    fn name(&self) -> &'static str {
+        match self {
+            Part::Ground => "Ground",
+            Part::Cliffs => "Cliffs",
+            Part::Water => "Water",
+            Part::Base => "Base",
+        }
+    }
+
+    fn material(&self, theme: &TerrainTheme) -> Handle<StandardMaterial> {
+        match self {
+            Part::Ground => theme.ground.clone(),
+            Part::Cliffs => theme.cliffs.clone(),
+            Part::Water => theme.water.clone(),
+            Part::Base => theme.base.clone(),
+        }
+    }
+
+    fn wireframe(&self, theme: &TerrainTheme) -> Option<Color> {
+        match self {
+            Part::Ground => theme.ground_wireframe,
+            Part::Cliffs => theme.cliffs_wireframe,
+            Part::Water | Part::Base => None,
+        }
+    }
+
+    fn is_solid(&self) -> bool {
+        *self != Part::Water
+    }
+}
+
+/// Which [`Part`] a child of a grid is, if any.
+type PartMarkers = (Entity, Has<Ground>, Has<Cliffs>, Has<Water>, Has<Base>);
+
+pub(super) fn apply_terrain_themes(
+    mut commands: Commands,
+    height_grid_q: Query<(Entity, &TerrainTheme, Option<&Children>), Changed<TerrainTheme>>,
+    part_q: Query<PartMarkers>,
+) {
+    for (entity, theme, children) in height_grid_q.iter() {
+        let existing: Vec<_> = part_q
+            .iter_many(children.into_iter().flatten())
+            .filter_map(|(child, ground, cliffs, water, base)| {
+                let part = match (ground, cliffs, water, base) {
+                    (true, ..) => Part::Ground,
+                    (_, true, ..) => Part::Cliffs,
+                    (_, _, true, _) => Part::Water,
+                    (.., true) => Part::Base,
+                    _ => return None,
+                };
+                Some((part, child))
+            })
+            .collect();
+
+        for part in PARTS {
+            let child = match existing.iter().find(|(other, _)| *other == part) {
+                Some(&(_, child)) => child,
+                None => {
+                    let child = spawn_part(&mut commands, part);
+                    commands.entity(entity).add_child(child);
+                    child
+                }
+            };
+            apply_part(&mut commands, child, part, theme);
+        }
+
+        commands.entity(entity).insert(RequiresMeshing);
+    }
+}
+
+fn spawn_part(commands: &mut Commands, part: Part) -> Entity {
+    let mut child = commands.spawn((PbrBundle::default(), Name::new(part.name())));
+    match part {
+        Part::Ground => child.insert((Terrain, Ground)),
+        Part::Cliffs => child.insert((Terrain, Cliffs)),
+        Part::Water => child.insert(Water),
+        Part::Base => child.insert((Terrain, Base)),
+    };
+    child.id()
+}
+
+fn apply_part(commands: &mut Commands, child: Entity, part: Part, theme: &TerrainTheme) {
+    let mut child = commands.entity(child);
+    child.insert(part.material(theme));
+
+    match part.wireframe(theme) {
+        Some(color) => child.insert((Wireframe, WireframeColor { color })),
+        None => child.remove::<(Wireframe, WireframeColor)>(),
+    };
+
+    if part.is_solid() && theme.colliders.is_some() {
+        child.insert(RigidBody::Static);
+    } else {
+        child.remove::<(RigidBody, Collider)>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::height_grid::{events::TerrainMeshed, mesh_builder::generate_meshes};
+
+    fn theme() -> TerrainTheme {
+        TerrainTheme {
+            ground: default(),
+            cliffs: default(),
+            water: default(),
+            base: default(),
+            ground_wireframe: Some(Color::WHITE),
+            cliffs_wireframe: None,
+            colliders: Some(ColliderConstructor::TrimeshFromMesh),
+        }
+    }
+
+    #[test]
+    fn theme_spawns_children() {
+        let mut app = App::new();
+        app.add_systems(Update, apply_terrain_themes);
+        let grid = HeightGrid::new((1, 1), [(0, 0, 0, 0).into()]);
+        let entity = app
+            .world_mut()
+            .spawn(HeightGridBundle::new(grid, theme()))
+            .id();
+
+        app.update();
+
+        let world = app.world();
+        let children = world.get::<Children>(entity).unwrap();
+        assert_eq!(children.len(), 4);
+        let count = |filter: fn(EntityRef) -> bool| {
+            children
+                .iter()
+                .filter(|&&child| filter(world.entity(child)))
+                .count()
+        };
+        assert_eq!(count(|child| child.contains::<Terrain>()), 3);
+        assert_eq!(count(|child| child.contains::<Wireframe>()), 1);
+        assert_eq!(count(|child| child.contains::<RigidBody>()), 3);
+    }
+
+    #[test]
+    fn changed_theme_updates_existing_children() {
+        let mut app = App::new();
+        app.add_systems(Update, apply_terrain_themes);
+        let grid = HeightGrid::new((1, 1), [(0, 0, 0, 0).into()]);
+        let entity = app
+            .world_mut()
+            .spawn(HeightGridBundle::new(grid, theme()))
+            .id();
+        app.update();
+
+        app.world_mut()
+            .get_mut::<TerrainTheme>(entity)
+            .unwrap()
+            .colliders = None;
+        app.update();
+
+        let world = app.world();
+        let children = world.get::<Children>(entity).unwrap();
+        assert_eq!(children.len(), 4);
+        assert!(children
+            .iter()
+            .all(|&child| !world.entity(child).contains::<RigidBody>()));
+    }
+
+    #[test]
+    fn water_gets_no_collider() {
+        let mut app = App::new();
+        app.init_resource::<Assets<Mesh>>()
+            .add_event::<TerrainMeshed>()
+            .add_systems(Update, (apply_terrain_themes, generate_meshes).chain());
+        let grid = HeightGrid::new((1, 1), [(0, 0, 0, 0).into()]);
+        let entity = app
+            .world_mut()
+            .spawn(HeightGridBundle::new(grid, theme()))
+            .id();
+
+        app.update();
+
+        let world = app.world();
+        for &child in world.get::<Children>(entity).unwrap() {
+            let child = world.entity(child);
+            assert_eq!(
+                child.contains::<ColliderConstructor>(),
+                !child.contains::<Water>()
+            );
+        }
+    }
+}
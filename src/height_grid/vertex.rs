@@ -0,0 +1,100 @@
+use bevy::math::{UVec2, Vec2};
+
+use super::{
+    coord::Coord,
+    corner::Corner,
+    flip::{FlipAxis, FlipCorner},
+};
+
+/// A point of the lattice the corners of the cells sit on. Vertex `(x, y)` is the bottom left
+/// corner of cell `(x, y)`, so a grid of `n` by `m` cells has `n + 1` by `m + 1` vertices.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Vertex(UVec2);
+
+impl Vertex {
+    pub const fn new(x: u32, y: u32) -> Self {
+        Self(UVec2::new(x, y))
+    }
+
+    pub const fn x(&self) -> u32 {
+        self.0.x
+    }
+
+    pub const fn y(&self) -> u32 {
+        self.0.y
+    }
+
+    pub const fn as_uvec2(&self) -> UVec2 {
+        self.0
+    }
+
+    /// The corners of the up to four cells meeting at the vertex, whether or not they are part of
+    /// a grid.
+    pub fn corners(&self) -> impl Iterator<Item = (Coord, Corner)> {
+        let bottom_left = (Coord::new(self.x(), self.y()), Corner::BottomLeft);
+        [
+            Some(bottom_left),
+            bottom_left.flip(FlipAxis::Horizontal),
+            bottom_left.flip(FlipAxis::Vertical),
+            bottom_left.flip(FlipAxis::Diagonal),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// The corner of `cell` at this vertex, if the vertex is one of its corners.
+    pub fn corner_of(&self, cell: impl Into<Coord>) -> Option<Corner> {
+        let cell = cell.into();
+        self.corners()
+            .find(|&(coord, _)| coord == cell)
+            .map(|(_, corner)| corner)
+    }
+
+    /// Position of the vertex on the grid plane.
+    pub fn position(&self) -> Vec2 {
+        self.0.as_vec2()
+    }
+}
+
+impl From<(Coord, Corner)> for Vertex {
+    fn from((coord, corner): (Coord, Corner)) -> Self {
+        Self(coord.as_uvec2() + corner.offset())
+    }
+}
+
+impl From<UVec2> for Vertex {
+    fn from(value: UVec2) -> Self {
+        Self(value)
+    }
+}
+
+impl From<(u32, u32)> for Vertex {
+    fn from((x, y): (u32, u32)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corners_round_trip() {
+        let vertex = Vertex::new(1, 1);
+        let corners: Vec<_> = vertex.corners().collect();
+
+        assert_eq!(corners.len(), 4);
+        assert!(corners
+            .iter()
+            .all(|&(coord, corner)| Vertex::from((coord, corner)) == vertex));
+        assert_eq!(vertex.corner_of((0, 0)), Some(Corner::TopRight));
+        assert_eq!(vertex.corner_of((1, 0)), Some(Corner::TopLeft));
+        assert_eq!(vertex.corner_of((2, 0)), None);
+    }
+
+    #[test]
+    fn corners_stop_at_the_origin() {
+        assert_eq!(Vertex::new(0, 0).corners().count(), 1);
+        assert_eq!(Vertex::new(0, 3).corners().count(), 2);
+    }
+}
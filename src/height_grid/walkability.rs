@@ -0,0 +1,306 @@
+use bevy::{
+    color::palettes::css::{GREEN, ORANGE, RED, YELLOW},
+    prelude::*,
+};
+
+use super::{
+    cell::CellMeshType,
+    cell_iter::CellRect,
+    coord::Coord,
+    corner::Corner,
+    direction::{Direction, DIRECTIONS},
+    events::HeightGridChanged,
+    pathfinding::{cell_center, edge_step, traverse, TraversalConfig},
+    HeightGrid,
+};
+
+pub struct WalkabilityPlugin;
+
+impl Plugin for WalkabilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TraversalConfig>()
+            .init_resource::<WalkabilityOverlay>()
+            .add_systems(
+                Update,
+                (
+                    update_walkability_maps,
+                    draw_walkability_overlay.after(update_walkability_maps),
+                ),
+            );
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CellClass {
+    Flat,
+    Sloped,
+    Steep,
+    CliffBordered,
+    Void,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CellWalkability {
+    pub mesh_type: CellMeshType,
+    pub class: CellClass,
+    /// Angle in radians between the steepest triangle of the cell and the ground plane.
+    pub slope_angle: f32,
+    /// Whether units can leave the cell over each edge, in the order of [`DIRECTIONS`].
+    pub passable: [bool; 4],
+}
+
+/// Per cell walkability of a [`HeightGrid`], kept up to date whenever the grid changes.
+#[derive(Component, Debug)]
+pub struct WalkabilityMap {
+    cells_count: UVec2,
+    cells: Vec<CellWalkability>,
+}
+
+impl WalkabilityMap {
+    pub fn new(grid: &HeightGrid, config: &TraversalConfig) -> Self {
+        let cells = (0..grid.cells_count.y)
+            .flat_map(|y| (0..grid.cells_count.x).map(move |x| Coord::new(x, y)))
+            .map(|coord| analyze_cell(grid, coord, config))
+            .collect();
+
+        Self {
+            cells_count: grid.cells_count,
+            cells,
+        }
+    }
+
+    /// Analyzes the cells around `region` again. The walkability of a cell depends on the
+    /// effective heights of its neighbours, which ramps and stairs take from their neighbours, so
+    /// cells up to two steps away from the region are affected.
+    pub fn update(&mut self, grid: &HeightGrid, region: CellRect, config: &TraversalConfig) {
+        for coord in region.grow(2, self.cells_count) {
+            let index = (self.cells_count.x * coord.y() + coord.x()) as usize;
+            self.cells[index] = analyze_cell(grid, coord, config);
+        }
+    }
+
+    pub fn get(&self, coord: impl Into<Coord>) -> &CellWalkability {
+        let UVec2 { x, y } = coord.into().as_uvec2();
+        assert!(x < self.cells_count.x);
+        assert!(y < self.cells_count.y);
+        &self.cells[(self.cells_count.x * y + x) as usize]
+    }
+
+    pub fn is_passable(&self, coord: impl Into<Coord>, direction: Direction) -> bool {
+        let index = DIRECTIONS
+            .iter()
+            .position(|&other| other == direction)
+            .expect("all directions are listed");
+        self.get(coord).passable[index]
+    }
+}
+
+fn analyze_cell(grid: &HeightGrid, coord: Coord, config: &TraversalConfig) -> CellWalkability {
+    let mesh_type = grid.cell_type(coord);
+    if grid.get_cell(coord).is_void() {
+        return CellWalkability {
+            mesh_type,
+            class: CellClass::Void,
+            slope_angle: 0.0,
+            passable: [false; 4],
+        };
+    }
+
+    let gradient = steepest_gradient(grid, coord, mesh_type);
+    let passable = DIRECTIONS.map(|direction| traverse(grid, coord, direction, config).is_some());
+    let cliff_bordered = DIRECTIONS.iter().any(|&direction| {
+        edge_step(grid, coord, direction).is_some_and(|step| step > 0)
+            || grid
+                .neighbour(coord, direction)
+                .is_some_and(|neighbour| grid.get_cell(neighbour).is_void())
+    });
+
+    // Ramps and stairs can always be walked, see `traverse`.
+    let feature = grid.get_cell(coord).feature().is_some();
+    let class = if gradient > config.max_slope && !feature {
+        CellClass::Steep
+    } else if cliff_bordered {
+        CellClass::CliffBordered
+    } else if gradient > 0.0 {
+        CellClass::Sloped
+    } else {
+        CellClass::Flat
+    };
+
+    CellWalkability {
+        mesh_type,
+        class,
+        slope_angle: gradient.atan(),
+        passable,
+    }
+}
+
+/// Height gained per unit of horizontal distance on the steepest triangle of the cell.
+fn steepest_gradient(grid: &HeightGrid, coord: Coord, mesh_type: CellMeshType) -> f32 {
+    use Corner::*;
+    let [tl, tr, bl, br] =
+        [TopLeft, TopRight, BottomLeft, BottomRight].map(|corner| grid.get_position(coord, corner));
+
+    let triangles = match mesh_type {
+        CellMeshType::Shared => return 0.0,
+        CellMeshType::Slash => [[tl, bl, tr], [tr, bl, br]],
+        CellMeshType::Backslash => [[tl, bl, br], [tl, br, tr]],
+    };
+
+    triangles
+        .iter()
+        .map(|&[a, b, c]| {
+            let normal = (b - a).cross(c - a);
+            normal.xy().length() / normal.z.abs()
+        })
+        .fold(0.0, f32::max)
+}
+
+fn update_walkability_maps(
+    mut commands: Commands,
+    config: Res<TraversalConfig>,
+    mut changed: EventReader<HeightGridChanged>,
+    mut height_grid_q: Query<(Entity, &HeightGrid, Option<&mut WalkabilityMap>)>,
+) {
+    for (entity, height_grid, map) in height_grid_q.iter_mut() {
+        let outdated = !map.is_some_and(|map| map.cells_count == height_grid.cells_count);
+        if config.is_changed() || outdated {
+            commands
+                .entity(entity)
+                .insert(WalkabilityMap::new(height_grid, &config));
+        }
+    }
+
+    for HeightGridChanged { entity, region, .. } in changed.read() {
+        if let Ok((_, height_grid, Some(mut map))) = height_grid_q.get_mut(*entity) {
+            if map.cells_count == height_grid.cells_count {
+                map.update(height_grid, *region, &config);
+            }
+        }
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct WalkabilityOverlay {
+    pub enabled: bool,
+}
+
+fn draw_walkability_overlay(
+    mut gizmos: Gizmos,
+    overlay: Res<WalkabilityOverlay>,
+    height_grid_q: Query<(&HeightGrid, &WalkabilityMap, &GlobalTransform)>,
+) {
+    if !overlay.enabled {
+        return;
+    }
+
+    let lift = Vec3::Z * 0.05;
+    for (height_grid, map, transform) in height_grid_q.iter() {
+        for y in 0..height_grid.cells_count.y {
+            for x in 0..height_grid.cells_count.x {
+                let coord = Coord::new(x, y);
+                let walkability = map.get(coord);
+                let color = match walkability.class {
+                    CellClass::Flat => GREEN,
+                    CellClass::Sloped => YELLOW,
+                    CellClass::Steep => RED,
+                    CellClass::CliffBordered => ORANGE,
+                    CellClass::Void => continue,
+                };
+                let center = transform.transform_point(cell_center(height_grid, coord) + lift);
+                gizmos.rect(
+                    center,
+                    transform.compute_transform().rotation,
+                    Vec2::splat(0.6),
+                    color,
+                );
+
+                for direction in DIRECTIONS {
+                    if map.is_passable(coord, direction) {
+                        continue;
+                    }
+                    let (a, b) = direction.corners();
+                    gizmos.line(
+                        transform.transform_point(height_grid.get_position(coord, a) + lift),
+                        transform.transform_point(height_grid.get_position(coord, b) + lift),
+                        RED,
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flat_cells_are_flat() {
+        let grid = HeightGrid::new((2, 1), vec![(1, 1, 1, 1).into(); 2]);
+        let map = WalkabilityMap::new(&grid, &TraversalConfig::default());
+
+        let cell = map.get((0, 0));
+        assert_eq!(cell.class, CellClass::Flat);
+        assert_eq!(cell.mesh_type, CellMeshType::Shared);
+        assert_eq!(cell.slope_angle, 0.0);
+        assert!(map.is_passable((0, 0), Direction::Right));
+        assert!(!map.is_passable((0, 0), Direction::Left));
+    }
+
+    #[test]
+    fn slopes_and_cliffs_are_classified() {
+        let grid = HeightGrid::new(
+            (3, 1),
+            [
+                (0, 1, 0, 1).into(),
+                (1, 1, 1, 1).into(),
+                (4, 4, 4, 4).into(),
+            ],
+        );
+        let map = WalkabilityMap::new(&grid, &TraversalConfig::default());
+
+        let slope = map.get((0, 0));
+        assert_eq!(slope.class, CellClass::Sloped);
+        assert!((slope.slope_angle - std::f32::consts::FRAC_PI_4).abs() < 1e-5);
+
+        let bordered = map.get((1, 0));
+        assert_eq!(bordered.class, CellClass::CliffBordered);
+        assert!(map.is_passable((1, 0), Direction::Left));
+        assert!(!map.is_passable((1, 0), Direction::Right));
+    }
+
+    #[test]
+    fn steep_cells_are_classified() {
+        let grid = HeightGrid::new((1, 1), [(0, 3, 0, 3).into()]);
+        let map = WalkabilityMap::new(&grid, &TraversalConfig::default());
+
+        assert_eq!(map.get((0, 0)).class, CellClass::Steep);
+    }
+
+    #[test]
+    fn void_cells_are_classified() {
+        let mut grid = HeightGrid::new((2, 1), vec![(0, 0, 0, 0).into(); 2]);
+        grid.set_void((1, 0), true);
+        let map = WalkabilityMap::new(&grid, &TraversalConfig::default());
+
+        assert_eq!(map.get((0, 0)).class, CellClass::CliffBordered);
+        assert!(!map.is_passable((0, 0), Direction::Right));
+        assert_eq!(map.get((1, 0)).class, CellClass::Void);
+        assert!(!map.is_passable((1, 0), Direction::Left));
+    }
+
+    #[test]
+    fn updating_a_region_matches_a_new_map() {
+        let config = TraversalConfig::default();
+        let mut grid = HeightGrid::new((4, 1), vec![(0, 0, 0, 0).into(); 4]);
+        let mut map = WalkabilityMap::new(&grid, &config);
+
+        grid.set_height((3, 0), Corner::TopLeft, 4);
+        map.update(&grid, CellRect::single((3, 0)), &config);
+
+        let fresh = WalkabilityMap::new(&grid, &config);
+        assert_eq!(map.cells, fresh.cells);
+        assert_eq!(map.get((2, 0)).class, CellClass::CliffBordered);
+    }
+}
@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+
+use super::{
+    coord::Coord, corner::CORNERS, direction::Direction, mesh_builder::RequiresMeshing,
+    region::CellRegion, HeightGrid,
+};
+
+pub struct WaterPlugin;
+
+impl Plugin for WaterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, remesh_changed_water);
+    }
+}
+
+/// Water of a [`HeightGrid`]. The global level floods every cell below it, regions only flood
+/// the cells connected to their seed.
+#[derive(Component, Debug, Default, Clone, Reflect)]
+#[reflect(Component)]
+pub struct WaterLevel {
+    pub global: Option<f32>,
+    pub regions: Vec<WaterRegion>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Reflect)]
+pub struct WaterRegion {
+    pub seed: Coord,
+    pub height: f32,
+}
+
+impl WaterLevel {
+    /// The water surface height of every cell in the grid, `None` for dry cells. Where regions
+    /// overlap the highest one wins.
+    pub fn surface_heights(&self, grid: &HeightGrid) -> Vec<Option<f32>> {
+        let mut surfaces: Vec<Option<f32>> = match self.global {
+            Some(global) => (0..grid.cells_count.y)
+                .flat_map(|y| (0..grid.cells_count.x).map(move |x| Coord::new(x, y)))
+                .map(|coord| {
+                    if grid.get_cell(coord).is_void() {
+                        return None;
+                    }
+                    let lowest = CORNERS
+                        .map(|corner| grid.get_height(coord, corner))
+                        .into_iter()
+                        .min();
+                    lowest
+                        .filter(|&lowest| (lowest as f32) < global)
+                        .map(|_| global)
+                })
+                .collect(),
+            None => vec![None; grid.cells.len()],
+        };
+
+        for region in self.regions.iter() {
+            for coord in flood_fill(grid, region.seed, region.height) {
+                let surface = &mut surfaces[grid.get_cell_index(coord)];
+                *surface = Some(surface.map_or(region.height, |s| s.max(region.height)));
+            }
+        }
+
+        surfaces
+    }
+}
+
+/// The cells water poured into `seed` spreads to when filled up to `height`.
+///
+/// Water only passes an edge where the terrain along it dips below `height`. Cliffs count with
+/// their higher side, so they hold the water back like a wall. Void cells never hold water.
+pub fn flood_fill(grid: &HeightGrid, seed: impl Into<Coord>, height: f32) -> Vec<Coord> {
+    let seed = seed.into();
+    if !grid.valid_coord(seed) || !below(grid, seed, height) {
+        return vec![];
+    }
+
+    CellRegion::flood(seed, |coord, direction| {
+        edge_below(grid, coord, direction, height)
+    })
+    .into_iter()
+    .collect()
+}
+
+fn below(grid: &HeightGrid, coord: Coord, height: f32) -> bool {
+    !grid.get_cell(coord).is_void()
+        && CORNERS
+            .iter()
+            .any(|&corner| (grid.get_height(coord, corner) as f32) < height)
+}
+
+fn edge_below(grid: &HeightGrid, coord: Coord, direction: Direction, height: f32) -> bool {
+    let Some(neighbour) = grid.neighbour(coord, direction) else {
+        return false;
+    };
+    let (a, b) = direction.corners();
+    let (opp_a, opp_b) = direction.opposite().corners();
+    if grid.get_cell(neighbour).is_void() {
+        return false;
+    }
+
+    [(a, opp_a), (b, opp_b)]
+        .iter()
+        .any(|&(corner, opp_corner)| {
+            let barrier = grid
+                .get_height(coord, corner)
+                .max(grid.get_height(neighbour, opp_corner));
+            (barrier as f32) < height
+        })
+}
+
+fn remesh_changed_water(
+    mut commands: Commands,
+    water_q: Query<Entity, (With<HeightGrid>, Changed<WaterLevel>)>,
+) {
+    for entity in water_q.iter() {
+        commands.entity(entity).insert(RequiresMeshing);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(heights: &[u32]) -> HeightGrid {
+        let cells: Vec<_> = heights.iter().map(|&h| (h, h, h, h).into()).collect();
+        HeightGrid::new((heights.len() as u32, 1), cells)
+    }
+
+    #[test]
+    fn flood_fill_stops_at_walls() {
+        let grid = row(&[0, 0, 3, 0]);
+
+        assert_eq!(
+            flood_fill(&grid, (0, 0), 1.0),
+            vec![Coord::new(0, 0), Coord::new(1, 0)]
+        );
+        assert_eq!(flood_fill(&grid, (0, 0), 4.0).len(), 4);
+    }
+
+    #[test]
+    fn flood_fill_ignores_dry_seed() {
+        let grid = row(&[2, 0]);
+
+        assert_eq!(flood_fill(&grid, (0, 0), 1.0), vec![]);
+    }
+
+    #[test]
+    fn flood_fill_follows_slopes() {
+        let grid = HeightGrid::new(
+            (3, 1),
+            [
+                (0, 0, 0, 0).into(),
+                (0, 2, 0, 2).into(),
+                (2, 2, 2, 2).into(),
+            ],
+        );
+
+        assert_eq!(flood_fill(&grid, (0, 0), 1.0).len(), 2);
+    }
+
+    #[test]
+    fn surface_heights_combine_global_and_regions() {
+        let grid = row(&[0, 3, 1]);
+        let water = WaterLevel {
+            global: Some(0.5),
+            regions: vec![WaterRegion {
+                seed: Coord::new(2, 0),
+                height: 2.0,
+            }],
+        };
+
+        assert_eq!(
+            water.surface_heights(&grid),
+            vec![Some(0.5), None, Some(2.0)]
+        );
+    }
+
+    #[test]
+    fn void_cells_stay_dry() {
+        let mut grid = row(&[0, 0, 0]);
+        grid.set_void((1, 0), true);
+        let water = WaterLevel {
+            global: Some(1.0),
+            regions: vec![],
+        };
+
+        assert_eq!(flood_fill(&grid, (0, 0), 1.0), vec![Coord::new(0, 0)]);
+        assert_eq!(flood_fill(&grid, (1, 0), 1.0), vec![]);
+        assert_eq!(
+            water.surface_heights(&grid),
+            vec![Some(1.0), None, Some(1.0)]
+        );
+    }
+}
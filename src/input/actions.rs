@@ -0,0 +1,372 @@
+use bevy::{
+    input::InputSystem,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::config::ConfigPlugin;
+
+/// Maps keys, mouse and gamepad buttons to [`Action`]s. Systems read the [`ActionState`] instead
+/// of the raw input, so that every input can be rebound.
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ConfigPlugin::<InputBindings>::new("config/bindings.ron"))
+            .init_resource::<ActionState>()
+            .register_type::<GamepadButtonType>()
+            .register_type::<GamepadAxisType>()
+            .add_systems(PreUpdate, update_action_state.after(InputSystem));
+    }
+}
+
+/// Something the player can do, triggered by any of its [`Binding`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum Action {
+    PanUp,
+    PanDown,
+    PanLeft,
+    PanRight,
+    /// Pans the camera with the mouse while held.
+    DragPan,
+    /// Orbits the camera with the mouse while held.
+    DragOrbit,
+    RotateLeft,
+    RotateRight,
+    ToggleProjection,
+    StoreBookmark(u8),
+    RecallBookmark(u8),
+    /// Raises the terrain, or applies the current tool.
+    EditRaise,
+    /// Lowers the terrain, or applies the opposite of the current tool.
+    EditLower,
+    /// Held to edit along the line from the previous edit.
+    EditStroke,
+    Undo,
+    Quit,
+}
+
+/// Number of camera bookmarks that have actions.
+pub const BOOKMARK_SLOTS: u8 = 9;
+
+impl Action {
+    /// Every action, in the order they are listed to the player.
+    pub fn all() -> impl Iterator<Item = Action> {
+        use Action::*;
+
+        [
+            PanUp,
+            PanDown,
+            PanLeft,
+            PanRight,
+            DragPan,
+            DragOrbit,
+            RotateLeft,
+            RotateRight,
+            ToggleProjection,
+            EditRaise,
+            EditLower,
+            EditStroke,
+            Undo,
+            Quit,
+        ]
+        .into_iter()
+        .chain((0..BOOKMARK_SLOTS).flat_map(|slot| [StoreBookmark(slot), RecallBookmark(slot)]))
+    }
+}
+
+/// An input that triggers an [`Action`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum Binding {
+    /// A key pressed while no Control key is held.
+    Key(KeyCode),
+    /// A key pressed while a Control key is held.
+    CtrlKey(KeyCode),
+    /// A mouse button pressed while no Control key is held.
+    Mouse(MouseButton),
+    /// A mouse button pressed while a Control key is held.
+    CtrlMouse(MouseButton),
+    /// A button of any gamepad.
+    GamepadButton(GamepadButtonType),
+    /// A stick of any gamepad pushed into the positive or negative direction of its axis.
+    GamepadAxis {
+        axis: GamepadAxisType,
+        positive: bool,
+    },
+}
+
+/// Stick movement below this is ignored.
+const AXIS_DEAD_ZONE: f32 = 0.15;
+
+/// The bindings of all actions, an action can have any number of them.
+#[derive(Resource, Debug, Clone, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct InputBindings {
+    pub bindings: Vec<(Action, Binding)>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        use Action::*;
+        use Binding::*;
+
+        let digits = [
+            KeyCode::Digit1,
+            KeyCode::Digit2,
+            KeyCode::Digit3,
+            KeyCode::Digit4,
+            KeyCode::Digit5,
+            KeyCode::Digit6,
+            KeyCode::Digit7,
+            KeyCode::Digit8,
+            KeyCode::Digit9,
+        ];
+        let axis = |axis, positive| GamepadAxis { axis, positive };
+
+        let mut bindings = vec![
+            (PanUp, Key(KeyCode::KeyW)),
+            (PanUp, Key(KeyCode::ArrowUp)),
+            (PanUp, GamepadButton(GamepadButtonType::DPadUp)),
+            (PanUp, axis(GamepadAxisType::LeftStickY, true)),
+            (PanDown, Key(KeyCode::KeyS)),
+            (PanDown, Key(KeyCode::ArrowDown)),
+            (PanDown, GamepadButton(GamepadButtonType::DPadDown)),
+            (PanDown, axis(GamepadAxisType::LeftStickY, false)),
+            (PanLeft, Key(KeyCode::KeyA)),
+            (PanLeft, Key(KeyCode::ArrowLeft)),
+            (PanLeft, GamepadButton(GamepadButtonType::DPadLeft)),
+            (PanLeft, axis(GamepadAxisType::LeftStickX, false)),
+            (PanRight, Key(KeyCode::KeyD)),
+            (PanRight, Key(KeyCode::ArrowRight)),
+            (PanRight, GamepadButton(GamepadButtonType::DPadRight)),
+            (PanRight, axis(GamepadAxisType::LeftStickX, true)),
+            (DragPan, Mouse(MouseButton::Right)),
+            (DragOrbit, Mouse(MouseButton::Middle)),
+            (RotateLeft, Key(KeyCode::KeyQ)),
+            (RotateLeft, GamepadButton(GamepadButtonType::LeftTrigger)),
+            (RotateRight, Key(KeyCode::KeyE)),
+            (RotateRight, GamepadButton(GamepadButtonType::RightTrigger)),
+            (ToggleProjection, Key(KeyCode::KeyO)),
+            (ToggleProjection, GamepadButton(GamepadButtonType::North)),
+            (EditRaise, Mouse(MouseButton::Left)),
+            (EditRaise, GamepadButton(GamepadButtonType::South)),
+            (EditLower, CtrlMouse(MouseButton::Left)),
+            (EditLower, GamepadButton(GamepadButtonType::East)),
+            (EditStroke, Key(KeyCode::ShiftLeft)),
+            (EditStroke, Key(KeyCode::ShiftRight)),
+            (Undo, CtrlKey(KeyCode::KeyZ)),
+            (Undo, GamepadButton(GamepadButtonType::West)),
+            (Quit, Key(KeyCode::Escape)),
+        ];
+        for (slot, digit) in (0..BOOKMARK_SLOTS).zip(digits) {
+            bindings.push((StoreBookmark(slot), CtrlKey(digit)));
+            bindings.push((RecallBookmark(slot), Key(digit)));
+        }
+
+        Self { bindings }
+    }
+}
+
+impl InputBindings {
+    /// The actions `binding` triggers.
+    pub fn actions(&self, binding: Binding) -> impl Iterator<Item = Action> + '_ {
+        self.bindings
+            .iter()
+            .filter(move |&&(_, bound)| bound == binding)
+            .map(|&(action, _)| action)
+    }
+}
+
+/// How far every action is pressed this and the previous frame.
+#[derive(Resource, Debug, Default)]
+pub struct ActionState {
+    current: HashMap<Action, f32>,
+    previous: HashMap<Action, f32>,
+    /// Actions that stay released until they are no longer pressed, see
+    /// [`ActionState::suppress`].
+    suppressed: HashSet<Action>,
+}
+
+impl ActionState {
+    /// How far the action is pressed from 0 to 1. Only sticks are in between.
+    pub fn value(&self, action: Action) -> f32 {
+        self.current.get(&action).copied().unwrap_or_default()
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.value(action) > 0.0
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.pressed(action) && self.previous.get(&action).is_none_or(|&value| value == 0.0)
+    }
+
+    pub fn any_just_pressed(&self, actions: impl IntoIterator<Item = Action>) -> bool {
+        actions.into_iter().any(|action| self.just_pressed(action))
+    }
+
+    /// Releases every pressed action until its inputs are released, for input that is meant for
+    /// something else, e.g. a binding being captured.
+    pub fn suppress(&mut self) {
+        self.suppressed
+            .extend(self.current.drain().map(|(action, _)| action));
+    }
+}
+
+pub fn update_action_state(
+    bindings: Res<InputBindings>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut state: ResMut<ActionState>,
+) {
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let pressed = |pressed: bool| if pressed { 1.0 } else { 0.0 };
+
+    let value = |binding: Binding| match binding {
+        Binding::Key(key) => pressed(!ctrl && keys.pressed(key)),
+        Binding::CtrlKey(key) => pressed(ctrl && keys.pressed(key)),
+        Binding::Mouse(button) => pressed(!ctrl && mouse_buttons.pressed(button)),
+        Binding::CtrlMouse(button) => pressed(ctrl && mouse_buttons.pressed(button)),
+        Binding::GamepadButton(button_type) => pressed(
+            gamepads
+                .iter()
+                .any(|gamepad| gamepad_buttons.pressed(GamepadButton::new(gamepad, button_type))),
+        ),
+        Binding::GamepadAxis { axis, positive } => gamepads
+            .iter()
+            .filter_map(|gamepad| gamepad_axes.get(GamepadAxis::new(gamepad, axis)))
+            .map(|value| if positive { value } else { -value })
+            .filter(|&value| value > AXIS_DEAD_ZONE)
+            .fold(0.0, f32::max)
+            .min(1.0),
+    };
+
+    let state = &mut *state;
+    state.previous = std::mem::take(&mut state.current);
+    for &(action, binding) in bindings.bindings.iter() {
+        let value = value(binding);
+        if value > 0.0 {
+            let current = state.current.entry(action).or_default();
+            *current = current.max(value);
+        }
+    }
+    let ActionState {
+        current,
+        suppressed,
+        ..
+    } = state;
+    suppressed.retain(|action| current.remove(action).is_some());
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::reflect::TypeRegistry;
+
+    use super::*;
+    use crate::config::{deserialize_config, serialize_config};
+
+    #[test]
+    fn bindings_round_trip() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<InputBindings>();
+        let mut bindings = InputBindings::default();
+        bindings
+            .bindings
+            .push((Action::Undo, Binding::Mouse(MouseButton::Back)));
+
+        let serialized = serialize_config(&bindings, &registry).unwrap();
+        assert_eq!(
+            deserialize_config::<InputBindings>(&serialized, &registry).unwrap(),
+            bindings
+        );
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.init_resource::<InputBindings>()
+            .init_resource::<ActionState>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<ButtonInput<MouseButton>>()
+            .init_resource::<Gamepads>()
+            .init_resource::<ButtonInput<GamepadButton>>()
+            .init_resource::<Axis<GamepadAxis>>()
+            .add_systems(Update, update_action_state);
+        app
+    }
+
+    #[test]
+    fn control_selects_the_bindings() {
+        let mut app = app();
+
+        let mut keys = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keys.press(KeyCode::Digit2);
+        app.update();
+        let state = app.world().resource::<ActionState>();
+        assert!(state.just_pressed(Action::RecallBookmark(1)));
+        assert!(!state.pressed(Action::StoreBookmark(1)));
+
+        let mut keys = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keys.press(KeyCode::ControlLeft);
+        app.update();
+        let state = app.world().resource::<ActionState>();
+        assert!(state.just_pressed(Action::StoreBookmark(1)));
+        assert!(!state.pressed(Action::RecallBookmark(1)));
+
+        app.update();
+        let state = app.world().resource::<ActionState>();
+        assert!(state.pressed(Action::StoreBookmark(1)));
+        assert!(!state.just_pressed(Action::StoreBookmark(1)));
+    }
+
+    #[test]
+    fn control_click_lowers() {
+        let mut app = app();
+
+        let mut keys = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keys.press(KeyCode::ControlLeft);
+        let mut mouse_buttons = app.world_mut().resource_mut::<ButtonInput<MouseButton>>();
+        mouse_buttons.press(MouseButton::Left);
+        app.update();
+        let state = app.world().resource::<ActionState>();
+        assert!(state.just_pressed(Action::EditLower));
+        assert!(!state.pressed(Action::EditRaise));
+    }
+
+    #[test]
+    fn default_bindings_trigger_one_action() {
+        let bindings = InputBindings::default();
+        for &(action, binding) in bindings.bindings.iter() {
+            assert_eq!(
+                bindings.actions(binding).collect::<Vec<_>>(),
+                [action],
+                "{binding:?} is bound more than once"
+            );
+        }
+    }
+
+    #[test]
+    fn suppressed_actions_wait_for_release() {
+        let mut app = app();
+
+        let mut keys = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keys.press(KeyCode::Escape);
+        app.update();
+        app.world_mut().resource_mut::<ActionState>().suppress();
+        app.update();
+        assert!(!app.world().resource::<ActionState>().pressed(Action::Quit));
+
+        let mut keys = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keys.release(KeyCode::Escape);
+        app.update();
+        let mut keys = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keys.press(KeyCode::Escape);
+        app.update();
+        assert!(app
+            .world()
+            .resource::<ActionState>()
+            .just_pressed(Action::Quit));
+    }
+}
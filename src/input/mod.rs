@@ -1,13 +1,21 @@
-use avian3d::spatial_query::{RayHitData, SpatialQuery, SpatialQueryFilter};
 use bevy::{color::palettes::css::WHITE, prelude::*};
 
-use crate::{camera::MainCamera, Terrain};
+use crate::{
+    camera::MainCamera,
+    height_grid::{
+        coord::Coord, corner::Corner, mesh_builder::MeshSettings, raycast::cast_ray,
+        theme::Surface, vertex::Vertex, HeightGrid,
+    },
+};
+
+pub mod actions;
 
 pub struct GameInputPlugin;
 
 impl Plugin for GameInputPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<TerrainRaycast>()
+        app.add_plugins(actions::ActionsPlugin)
+            .init_resource::<TerrainRaycast>()
             .init_resource::<CurrentMousePos>()
             .add_systems(
                 PreUpdate,
@@ -29,9 +37,18 @@ pub struct HitPoint {
     pub position: Vec3,
     pub normal: Vec3,
     pub entity: Entity,
+    pub surface: Surface,
+    /// The cell of the grid that was hit. Cliffs belong to the cell behind them.
+    pub cell: Coord,
+    /// The corner of [`HitPoint::cell`] closest to the hit.
+    pub corner: Corner,
+    /// The vertex of [`HitPoint::corner`].
+    pub vertex: Vertex,
 }
 #[derive(Debug, Resource, Default)]
 pub struct TerrainRaycast {
+    /// The ray from the camera through the cursor.
+    pub ray: Option<Ray3d>,
     pub hit_point: Option<HitPoint>,
 }
 
@@ -45,8 +62,7 @@ fn update_current_mouse_pos(
 }
 
 fn raycast(
-    spatial_query: SpatialQuery,
-    terrain: Query<(&Parent, &Terrain)>,
+    height_grid_q: Query<(Entity, &HeightGrid, Option<&MeshSettings>, &GlobalTransform)>,
     main_camera: Query<(&GlobalTransform, &Camera), With<MainCamera>>,
     mouse_position: Res<CurrentMousePos>,
     mut terrain_raycast: ResMut<TerrainRaycast>,
@@ -54,38 +70,34 @@ fn raycast(
     let (t, camera) = main_camera
         .get_single()
         .expect("only one main camera allowed");
-    let Ray3d { origin, direction } = camera
+    let ray = camera
         .viewport_to_world(t, mouse_position.position)
         .unwrap_or(Ray3d::new(t.translation(), *t.forward()));
+    let Ray3d { origin, direction } = ray;
+    terrain_raycast.ray = Some(ray);
 
-    terrain_raycast.hit_point = spatial_query
-        .cast_ray_predicate(
-            origin,
-            direction,
-            f32::MAX,
-            true,
-            SpatialQueryFilter::default(),
-            &|entity| terrain.get(entity).is_ok(),
-        )
-        .map(
-            |RayHitData {
-                 time_of_impact,
-                 normal,
-                 entity,
-             }| {
-                let position = origin + time_of_impact * direction;
-
-                let (ref parent, _) = terrain
-                    .get(entity)
-                    .expect(format!("terrain {} does not have parent", entity).as_str());
-
-                HitPoint {
-                    position,
-                    normal,
-                    entity: ***parent,
-                }
-            },
-        );
+    terrain_raycast.hit_point = height_grid_q
+        .iter()
+        .filter_map(|(entity, height_grid, settings, transform)| {
+            let to_local = transform.affine().inverse();
+            let hit = cast_ray(
+                height_grid,
+                &settings.copied().unwrap_or_default(),
+                to_local.transform_point3(origin),
+                to_local.transform_vector3(*direction),
+            )?;
+            Some((entity, transform, hit))
+        })
+        .min_by(|(.., a), (.., b)| a.distance.total_cmp(&b.distance))
+        .map(|(entity, transform, hit)| HitPoint {
+            position: transform.transform_point(hit.position),
+            normal: transform.compute_transform().rotation * hit.normal,
+            entity,
+            surface: hit.surface,
+            cell: hit.cell,
+            corner: hit.corner,
+            vertex: Vertex::from((hit.cell, hit.corner)),
+        });
 }
 
 fn terrain_gizmo(mut gizmos: Gizmos, terrain_raycast: Res<TerrainRaycast>) {
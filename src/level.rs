@@ -0,0 +1,320 @@
+use std::{error::Error, fs, path::PathBuf};
+
+use bevy::{
+    ecs::entity::EntityHashMap,
+    prelude::*,
+    scene::{ron, serde::SceneDeserializer},
+};
+
+use crate::{
+    camera::bookmarks::CameraBookmarks,
+    height_grid::{
+        mesh_builder::{MeshSettings, RequiresMeshing},
+        theme::{Base, Cliffs, Ground, Terrain, Water},
+        water::WaterLevel,
+        HeightGrid, TerrainTheme,
+    },
+};
+
+/// Saves and loads levels as scene files. Meshes, materials and colliders are not saved, they are
+/// rebuilt from the grids after loading. The camera bookmarks are saved along with the level.
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<LevelObject>()
+            .add_event::<SaveLevel>()
+            .add_event::<LoadLevel>()
+            .add_systems(Update, (save_level, load_level));
+    }
+}
+
+/// Marks objects placed in the level so that they are saved with it, together with their
+/// children. Only their transform, name and hierarchy are saved, anything else has to be added
+/// back by the systems that spawn them, e.g. when a [`LevelObject`] is added.
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct LevelObject;
+
+/// Saves every grid and level object with their children and every light to a file.
+#[derive(Event, Debug, Clone)]
+pub struct SaveLevel(pub PathBuf);
+
+/// Replaces the grids, level objects and lights of the world with those saved in a file.
+#[derive(Event, Debug, Clone)]
+pub struct LoadLevel(pub PathBuf);
+
+/// Theme given to loaded grids, as materials are not part of the level file.
+#[derive(Resource, Debug, Clone)]
+pub struct LevelTheme(pub TerrainTheme);
+
+type LevelResult<T> = Result<T, Box<dyn Error>>;
+
+fn save_level(world: &mut World) {
+    let requests: Vec<_> = world.resource_mut::<Events<SaveLevel>>().drain().collect();
+    for SaveLevel(path) in requests {
+        let saved = serialize_level(world).and_then(|level| {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            Ok(fs::write(&path, level)?)
+        });
+        match saved {
+            Ok(()) => info!("Saved level to {}", path.display()),
+            Err(err) => error!("Could not save level to {}: {err}", path.display()),
+        }
+    }
+}
+
+fn load_level(world: &mut World) {
+    let requests: Vec<_> = world.resource_mut::<Events<LoadLevel>>().drain().collect();
+    for LoadLevel(path) in requests {
+        let loaded = fs::read_to_string(&path)
+            .map_err(Into::into)
+            .and_then(|level| deserialize_level(world, &level));
+        match loaded {
+            Ok(()) => info!("Loaded level from {}", path.display()),
+            Err(err) => error!("Could not load level from {}: {err}", path.display()),
+        }
+    }
+}
+
+type Light = Or<(With<PointLight>, With<DirectionalLight>, With<SpotLight>)>;
+
+/// The grids and level objects with all their descendants and the lights.
+fn level_entities(world: &mut World) -> Vec<Entity> {
+    let mut entities: Vec<Entity> = world
+        .query_filtered::<Entity, Or<(With<HeightGrid>, With<LevelObject>)>>()
+        .iter(world)
+        .collect();
+    let mut index = 0;
+    while let Some(&entity) = entities.get(index) {
+        if let Some(children) = world.get::<Children>(entity) {
+            let new: Vec<_> = children
+                .iter()
+                .filter(|child| !entities.contains(child))
+                .copied()
+                .collect();
+            entities.extend(new);
+        }
+        index += 1;
+    }
+
+    entities.extend(
+        world
+            .query_filtered::<Entity, Light>()
+            .iter(world)
+            .filter(|entity| !entities.contains(entity))
+            .collect::<Vec<_>>(),
+    );
+    entities
+}
+
+fn serialize_level(world: &mut World) -> LevelResult<String> {
+    let entities = level_entities(world);
+    let scene = DynamicSceneBuilder::from_world(world)
+        .deny_all()
+        .allow::<HeightGrid>()
+        .allow::<WaterLevel>()
+        .allow::<MeshSettings>()
+        .allow::<Terrain>()
+        .allow::<Ground>()
+        .allow::<Cliffs>()
+        .allow::<Water>()
+        .allow::<Base>()
+        .allow::<LevelObject>()
+        .allow::<Transform>()
+        .allow::<Name>()
+        .allow::<Parent>()
+        .allow::<Children>()
+        .allow::<PointLight>()
+        .allow::<DirectionalLight>()
+        .allow::<SpotLight>()
+        .extract_entities(entities.into_iter())
+        .deny_all_resources()
+        .allow_resource::<CameraBookmarks>()
+        .extract_resources()
+        .build();
+
+    let registry = world.resource::<AppTypeRegistry>().read();
+    Ok(scene.serialize(&registry)?)
+}
+
+fn deserialize_level(world: &mut World, level: &str) -> LevelResult<()> {
+    let scene = {
+        let registry = world.resource::<AppTypeRegistry>().read();
+        ron::Options::default().from_str_seed(
+            level,
+            SceneDeserializer {
+                type_registry: &registry,
+            },
+        )?
+    };
+
+    let roots: Vec<Entity> = level_entities(world)
+        .into_iter()
+        .filter(|&entity| world.get::<Parent>(entity).is_none())
+        .collect();
+    for entity in roots {
+        world.entity_mut(entity).despawn_recursive();
+    }
+
+    let mut entity_map = EntityHashMap::default();
+    scene.write_to_world(world, &mut entity_map)?;
+
+    let theme = world
+        .get_resource::<LevelTheme>()
+        .map(|theme| theme.0.clone());
+    for &entity in entity_map.values() {
+        restore(world, entity, theme.as_ref());
+    }
+
+    Ok(())
+}
+
+/// Adds back everything a loaded entity needs that is not saved in the level.
+fn restore(world: &mut World, entity: Entity, theme: Option<&TerrainTheme>) {
+    let mut entity = world.entity_mut(entity);
+    let transform = entity.get::<Transform>().copied().unwrap_or_default();
+
+    if entity.contains::<HeightGrid>() {
+        entity.insert((SpatialBundle::from_transform(transform), RequiresMeshing));
+        if let Some(theme) = theme {
+            entity.insert(theme.clone());
+        }
+    } else if entity.contains::<Ground>()
+        || entity.contains::<Cliffs>()
+        || entity.contains::<Water>()
+        || entity.contains::<Base>()
+    {
+        entity.insert(PbrBundle {
+            transform,
+            ..default()
+        });
+    } else if let Some(point_light) = entity.get::<PointLight>().cloned() {
+        entity.insert(PointLightBundle {
+            point_light,
+            transform,
+            ..default()
+        });
+    } else if let Some(directional_light) = entity.get::<DirectionalLight>().cloned() {
+        entity.insert(DirectionalLightBundle {
+            directional_light,
+            transform,
+            ..default()
+        });
+    } else if let Some(spot_light) = entity.get::<SpotLight>().cloned() {
+        entity.insert(SpotLightBundle {
+            spot_light,
+            transform,
+            ..default()
+        });
+    } else if entity.contains::<LevelObject>() {
+        entity.insert(SpatialBundle::from_transform(transform));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::bookmarks::Bookmark;
+
+    fn world() -> World {
+        let mut app = App::new();
+        app.register_type::<HeightGrid>()
+            .register_type::<WaterLevel>()
+            .register_type::<MeshSettings>()
+            .register_type::<Ground>()
+            .register_type::<Terrain>()
+            .register_type::<PointLight>()
+            .register_type::<Transform>()
+            .register_type::<Name>()
+            .register_type::<Parent>()
+            .register_type::<Children>()
+            .register_type::<CameraBookmarks>()
+            .register_type::<LevelObject>();
+        std::mem::take(app.world_mut())
+    }
+
+    #[test]
+    fn level_round_trip() {
+        let mut world = world();
+        let grid = HeightGrid::new((2, 1), [(0, 1, 2, 3).into(), (4, 4, 4, 4).into()]);
+        let ground = world
+            .spawn((PbrBundle::default(), Terrain, Ground, Name::new("Ground")))
+            .id();
+        world
+            .spawn((
+                grid.clone(),
+                WaterLevel::default(),
+                MeshSettings::default(),
+                SpatialBundle::default(),
+            ))
+            .add_child(ground);
+        world.spawn(PointLightBundle {
+            transform: Transform::from_xyz(1.0, 2.0, 3.0),
+            ..default()
+        });
+
+        let level = serialize_level(&mut world).unwrap();
+        assert!(!level.contains("Handle"));
+
+        deserialize_level(&mut world, &level).unwrap();
+
+        let mut grid_q = world.query::<(&HeightGrid, &Children, Has<RequiresMeshing>)>();
+        let (loaded, children, requires_meshing) = grid_q.single(&world);
+        assert_eq!(loaded.cells, grid.cells);
+        assert!(requires_meshing);
+        assert_eq!(children.len(), 1);
+        let child = world.entity(children[0]);
+        assert!(child.contains::<Ground>() && child.contains::<Handle<Mesh>>());
+
+        let mut light_q = world.query::<(&PointLight, &Transform)>();
+        let (_, transform) = light_q.single(&world);
+        assert_eq!(transform.translation, Vec3::new(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn level_objects_round_trip() {
+        let mut world = world();
+        let transform = Transform::from_xyz(1.0, 2.0, 0.5);
+        world.spawn((
+            SpatialBundle::from_transform(transform),
+            LevelObject,
+            Name::new("Tree"),
+        ));
+        let unmarked = world.spawn(SpatialBundle::default()).id();
+
+        let level = serialize_level(&mut world).unwrap();
+        assert!(level.contains("Tree"));
+
+        deserialize_level(&mut world, &level).unwrap();
+
+        let mut object_q =
+            world.query_filtered::<(&Name, &Transform, Has<GlobalTransform>), With<LevelObject>>();
+        let (name, loaded, spatial) = object_q.single(&world);
+        assert_eq!(name.as_str(), "Tree");
+        assert_eq!(*loaded, transform);
+        assert!(spatial);
+        assert!(world.get_entity(unmarked).is_some());
+        assert_eq!(world.query::<&Transform>().iter(&world).count(), 2);
+    }
+
+    #[test]
+    fn bookmarks_round_trip() {
+        let mut world = world();
+        let mut bookmarks = CameraBookmarks::default();
+        bookmarks.slots[2] = Some(Bookmark {
+            translation: Vec3::new(4.0, 5.0, 1.0),
+            rotation: Quat::from_rotation_z(1.0),
+            zoom: 30.0,
+        });
+        world.insert_resource(bookmarks.clone());
+
+        let level = serialize_level(&mut world).unwrap();
+        world.insert_resource(CameraBookmarks::default());
+        deserialize_level(&mut world, &level).unwrap();
+
+        assert_eq!(world.resource::<CameraBookmarks>().slots, bookmarks.slots);
+    }
+}
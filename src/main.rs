@@ -1,14 +1,16 @@
 mod camera;
 mod close_on_esc;
+mod config;
 mod height_grid;
 mod input;
+mod level;
 mod terrain_editor;
 
 use avian3d::prelude::*;
 use bevy::{
     color::palettes::css::{GHOST_WHITE, LIME},
     core::FrameCount,
-    pbr::wireframe::{Wireframe, WireframeColor, WireframePlugin},
+    pbr::wireframe::WireframePlugin,
     prelude::*,
     render::{
         settings::{RenderCreation, WgpuFeatures, WgpuSettings},
@@ -18,16 +20,11 @@ use bevy::{
 
 use bevy_egui::EguiPlugin;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
-use height_grid::{mesh_builder::RequiresMeshing, HeightGrid};
-
-#[derive(Component, Debug)]
-pub struct Terrain;
-
-#[derive(Component, Debug)]
-pub struct Ground;
-
-#[derive(Component, Debug)]
-pub struct Cliffs;
+use height_grid::{
+    mesh_builder::{MeshSettings, SkirtSettings},
+    HeightGrid, HeightGridBundle, TerrainTheme,
+};
+use level::LevelTheme;
 
 fn main() {
     App::new()
@@ -55,6 +52,7 @@ fn main() {
             PhysicsPlugins::default(),
             input::GameInputPlugin,
             height_grid::HeightGridPlugin,
+            level::LevelPlugin,
             terrain_editor::TerrainEditorPlugin,
             EguiPlugin,
             WorldInspectorPlugin::new(),
@@ -71,71 +69,54 @@ fn setup(
 ) {
     let ground_texture = asset_server.load("textures/grass.png");
     let cliffs_texture = asset_server.load("textures/dirt.png");
-    let ground_material = materials.add(StandardMaterial {
-        base_color_texture: Some(ground_texture.clone()),
-        ..default()
-    });
-
-    let cliffs_material = materials.add(StandardMaterial {
-        base_color_texture: Some(cliffs_texture.clone()),
-        ..default()
-    });
-
-    let ground_id = commands
-        .spawn((
-            PbrBundle {
-                material: ground_material,
-                transform: Transform::IDENTITY,
-                ..default()
-            },
-            Terrain,
-            Ground,
-            Wireframe,
-            WireframeColor { color: LIME.into() },
-            RigidBody::Static,
-            Name::new("Ground"),
-        ))
-        .id();
-
-    let cliffs_id = commands
-        .spawn((
-            PbrBundle {
-                material: cliffs_material,
-                transform: Transform::IDENTITY,
-                ..default()
-            },
-            Terrain,
-            Cliffs,
-            Wireframe,
-            WireframeColor {
-                color: GHOST_WHITE.into(),
-            },
-            RigidBody::Static,
-            Name::new("Cliffs"),
-        ))
-        .id();
+    let theme = TerrainTheme {
+        ground: materials.add(StandardMaterial {
+            base_color_texture: Some(ground_texture.clone()),
+            ..default()
+        }),
+        cliffs: materials.add(StandardMaterial {
+            base_color_texture: Some(cliffs_texture.clone()),
+            ..default()
+        }),
+        water: materials.add(StandardMaterial {
+            base_color: Color::srgba(0.1, 0.35, 0.8, 0.6),
+            alpha_mode: AlphaMode::Blend,
+            perceptual_roughness: 0.1,
+            ..default()
+        }),
+        base: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.35, 0.28, 0.22),
+            perceptual_roughness: 0.9,
+            ..default()
+        }),
+        ground_wireframe: Some(LIME.into()),
+        cliffs_wireframe: Some(GHOST_WHITE.into()),
+        colliders: Some(ColliderConstructor::TrimeshFromMesh),
+    };
 
-    let mut height_grid = commands.spawn((
-        RequiresMeshing,
-        SpatialBundle::default(),
-        HeightGrid::new(
-            (3, 3),
-            [
-                (0, 0, 0, 0).into(),
-                (0, 0, 0, 0).into(),
-                (0, 0, 0, 0).into(),
-                (0, 1, 0, 1).into(),
-                (1, 1, 1, 1).into(),
-                (1, 0, 1, 0).into(),
-                (0, 0, 0, 1).into(),
-                (0, 0, 1, 1).into(),
-                (0, 0, 1, 0).into(),
-            ],
-        ),
-        Name::new("Height Grid"),
-    ));
+    let height_grid = HeightGrid::new(
+        (3, 3),
+        [
+            (0, 0, 0, 0).into(),
+            (0, 0, 0, 0).into(),
+            (0, 0, 0, 0).into(),
+            (0, 1, 0, 1).into(),
+            (1, 1, 1, 1).into(),
+            (1, 0, 1, 0).into(),
+            (0, 0, 0, 1).into(),
+            (0, 0, 1, 1).into(),
+            (0, 0, 1, 0).into(),
+        ],
+    );
 
-    height_grid.push_children(&[ground_id, cliffs_id]);
+    commands.insert_resource(LevelTheme(theme.clone()));
+    commands.spawn(HeightGridBundle {
+        mesh_settings: MeshSettings {
+            skirt: Some(SkirtSettings::default()),
+            ..default()
+        },
+        ..HeightGridBundle::new(height_grid, theme)
+    });
 
     commands.spawn(PointLightBundle {
         point_light: PointLight {
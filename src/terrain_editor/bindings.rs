@@ -0,0 +1,189 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_egui::EguiContexts;
+
+use crate::{
+    config::{LoadConfig, SaveConfig},
+    input::actions::{Action, ActionState, Binding, InputBindings},
+};
+
+/// How far a stick has to be pushed to be bound.
+const CAPTURE_AXIS_THRESHOLD: f32 = 0.5;
+
+/// The binding waiting for the next input.
+#[derive(Resource, Debug, Default)]
+pub(super) struct BindingCapture {
+    /// The action to bind and the index of the binding it replaces, `None` adds a binding.
+    target: Option<(Action, Option<usize>)>,
+}
+
+fn label(binding: Binding) -> String {
+    match binding {
+        Binding::Key(key) => format!("{key:?}"),
+        Binding::CtrlKey(key) => format!("Ctrl+{key:?}"),
+        Binding::Mouse(button) => format!("Mouse {button:?}"),
+        Binding::CtrlMouse(button) => format!("Ctrl+Mouse {button:?}"),
+        Binding::GamepadButton(button) => format!("Pad {button:?}"),
+        Binding::GamepadAxis { axis, positive } => {
+            format!("Pad {axis:?}{}", if positive { "+" } else { "-" })
+        }
+    }
+}
+
+pub(super) fn bindings_ui(
+    mut contexts: EguiContexts,
+    mut bindings: ResMut<InputBindings>,
+    mut capture: ResMut<BindingCapture>,
+    mut save_events: EventWriter<SaveConfig<InputBindings>>,
+    mut load_events: EventWriter<LoadConfig<InputBindings>>,
+) {
+    use bevy_egui::egui;
+
+    egui::Window::new("Input Bindings")
+        .default_open(false)
+        .show(contexts.ctx_mut(), |ui| {
+            let mut removed = None;
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for action in Action::all() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{action:?}"));
+                        let bound = bindings
+                            .bindings
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, (bound, _))| *bound == action);
+                        for (index, &(_, binding)) in bound {
+                            let capturing = capture.target == Some((action, Some(index)));
+                            let mut text = egui::RichText::new(if capturing {
+                                "...".into()
+                            } else {
+                                label(binding)
+                            });
+                            let mut hover = "Right click to remove".to_string();
+                            let others: Vec<_> = bindings
+                                .actions(binding)
+                                .filter(|&other| other != action)
+                                .collect();
+                            if !others.is_empty() {
+                                text = text.color(ui.visuals().warn_fg_color);
+                                hover = format!("Also bound to {others:?}\n{hover}");
+                            }
+                            let response = ui.button(text).on_hover_text(hover);
+                            if response.clicked() {
+                                capture.target = Some((action, Some(index)));
+                            } else if response.secondary_clicked() {
+                                removed = Some(index);
+                            }
+                        }
+                        let adding = capture.target == Some((action, None));
+                        if ui.button(if adding { "..." } else { "+" }).clicked() {
+                            capture.target = Some((action, None));
+                        }
+                    });
+                }
+            });
+            if let Some(index) = removed {
+                bindings.bindings.remove(index);
+                capture.target = None;
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    save_events.send(default());
+                }
+                if ui.button("Load").clicked() {
+                    load_events.send(default());
+                }
+                if ui.button("Reset").clicked() {
+                    *bindings = InputBindings::default();
+                    capture.target = None;
+                }
+                if capture.target.is_some() && ui.button("Cancel").clicked() {
+                    capture.target = None;
+                }
+            });
+        });
+}
+
+/// The inputs bindings are captured from.
+#[derive(SystemParam)]
+pub(super) struct CaptureInputs<'w> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    mouse_buttons: Res<'w, ButtonInput<MouseButton>>,
+    gamepad_buttons: Res<'w, ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<'w, Axis<GamepadAxis>>,
+}
+
+/// Binds the next pressed key, button or pushed stick to the action waiting in
+/// [`BindingCapture`]. Actions are suppressed while capturing, so the captured input does not
+/// trigger them. Clicks on the UI are not captured, so that capturing can be cancelled.
+pub(super) fn capture_binding(
+    mut contexts: EguiContexts,
+    mut capture: ResMut<BindingCapture>,
+    mut bindings: ResMut<InputBindings>,
+    mut actions: ResMut<ActionState>,
+    inputs: CaptureInputs,
+) {
+    let Some((action, index)) = capture.target else {
+        return;
+    };
+    actions.suppress();
+    let CaptureInputs {
+        keys,
+        mouse_buttons,
+        gamepad_buttons,
+        gamepad_axes,
+    } = inputs;
+
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let key = keys
+        .get_just_pressed()
+        .find(|key| !matches!(key, KeyCode::ControlLeft | KeyCode::ControlRight))
+        .map(|&key| {
+            if ctrl {
+                Binding::CtrlKey(key)
+            } else {
+                Binding::Key(key)
+            }
+        });
+    let over_ui = contexts.ctx_mut().is_pointer_over_area();
+    let mouse = || {
+        mouse_buttons
+            .get_just_pressed()
+            .find(|_| !over_ui)
+            .map(|&button| {
+                if ctrl {
+                    Binding::CtrlMouse(button)
+                } else {
+                    Binding::Mouse(button)
+                }
+            })
+    };
+    let gamepad_button = || {
+        gamepad_buttons
+            .get_just_pressed()
+            .next()
+            .map(|button| Binding::GamepadButton(button.button_type))
+    };
+    let gamepad_axis = || {
+        gamepad_axes.devices().find_map(|&axis| {
+            let value = gamepad_axes.get(axis)?;
+            (value.abs() > CAPTURE_AXIS_THRESHOLD).then_some(Binding::GamepadAxis {
+                axis: axis.axis_type,
+                positive: value > 0.0,
+            })
+        })
+    };
+    let Some(binding) = key
+        .or_else(mouse)
+        .or_else(gamepad_button)
+        .or_else(gamepad_axis)
+    else {
+        return;
+    };
+
+    match index.and_then(|index| bindings.bindings.get_mut(index)) {
+        Some(bound) => *bound = (action, binding),
+        None => bindings.bindings.push((action, binding)),
+    }
+    capture.target = None;
+}
@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+use crate::{
+    camera::settings::{CameraSettings, PitchCurve, MAX_ZOOM, MIN_ZOOM},
+    config::{LoadConfig, SaveConfig},
+};
+
+pub(super) fn camera_settings_ui(
+    mut contexts: EguiContexts,
+    mut settings: ResMut<CameraSettings>,
+    mut save_events: EventWriter<SaveConfig<CameraSettings>>,
+    mut load_events: EventWriter<LoadConfig<CameraSettings>>,
+) {
+    use bevy_egui::egui;
+
+    egui::Window::new("Camera Settings")
+        .default_open(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Zoom Speed");
+                ui.add(egui::DragValue::new(&mut settings.zoom_speed).speed(0.05));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Zoom Range");
+                let max_zoom = settings.max_zoom;
+                ui.add(egui::DragValue::new(&mut settings.min_zoom).range(MIN_ZOOM..=max_zoom));
+                let min_zoom = settings.min_zoom;
+                ui.add(egui::DragValue::new(&mut settings.max_zoom).range(min_zoom..=MAX_ZOOM));
+            });
+            let pitch_changed = ui
+                .horizontal(|ui| {
+                    ui.label("Pitch Range");
+                    let min = ui.drag_angle(&mut settings.min_pitch);
+                    let max = ui.drag_angle(&mut settings.max_pitch);
+                    min.changed() || max.changed()
+                })
+                .inner;
+            if pitch_changed {
+                settings.sanitize();
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("Pitch Curve");
+                ui.radio_value(&mut settings.pitch_curve, PitchCurve::Linear, "Linear");
+                ui.radio_value(&mut settings.pitch_curve, PitchCurve::Smooth, "Smooth");
+                let power = matches!(settings.pitch_curve, PitchCurve::Power(_));
+                if ui.radio(power, "Power").clicked() && !power {
+                    settings.pitch_curve = PitchCurve::Power(2.0);
+                }
+                if let PitchCurve::Power(exponent) = &mut settings.pitch_curve {
+                    ui.add(egui::DragValue::new(exponent).speed(0.05).range(0.1..=10.0));
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Scroll Line Pixels");
+                ui.add(egui::DragValue::new(&mut settings.scroll_line_pixels).speed(0.5));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Pan Speed");
+                ui.add(egui::Slider::new(&mut settings.pan_speed, 0.0..=2.0));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Smoothing");
+                ui.add(egui::Slider::new(&mut settings.smoothing, 1.0..=50.0));
+            });
+
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    save_events.send(default());
+                }
+                if ui.button("Load").clicked() {
+                    load_events.send(default());
+                }
+                if ui.button("Reset").clicked() {
+                    *settings = CameraSettings::default();
+                }
+            });
+        });
+}
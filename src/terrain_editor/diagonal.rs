@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+
+use crate::{
+    height_grid::{
+        cell::{CellMeshType, Diagonal},
+        HeightGrid,
+    },
+    input::{
+        actions::{Action, ActionState},
+        TerrainRaycast,
+    },
+};
+
+use super::{history::EditHistory, EditConfig, EditMode};
+
+/// Clicking a cell splits it along the other diagonal. Flipping back to the diagonal the mesh
+/// builder would pick anyway removes the override again.
+pub(super) fn flip_diagonal(
+    edit_config: Res<EditConfig>,
+    terrain_raycast: Res<TerrainRaycast>,
+    mut height_grid_q: Query<&mut HeightGrid>,
+    actions: Res<ActionState>,
+    mut history: ResMut<EditHistory>,
+) {
+    if edit_config.mode != EditMode::FlipDiagonal
+        || !actions.any_just_pressed([Action::EditRaise, Action::EditLower])
+    {
+        return;
+    }
+    let Some(hit_point) = terrain_raycast.hit_point else {
+        return;
+    };
+    let coord = hit_point.cell;
+    let Ok(mut height_grid) = height_grid_q.get_mut(hit_point.entity) else {
+        return;
+    };
+
+    let flipped = match height_grid.cell_type(coord) {
+        CellMeshType::Shared => return,
+        CellMeshType::Slash => Diagonal::Backslash,
+        CellMeshType::Backslash => Diagonal::Slash,
+    };
+    let automatic = height_grid.automatic_cell_type(coord);
+    history.record(hit_point.entity, &height_grid);
+    height_grid.set_diagonal(coord, (automatic != flipped.into()).then_some(flipped));
+}
@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+use crate::{
+    height_grid::{
+        cell::CellFeature,
+        direction::{Direction, DIRECTIONS},
+        HeightGrid,
+    },
+    input::{
+        actions::{Action, ActionState},
+        TerrainRaycast,
+    },
+};
+
+use super::{history::EditHistory, EditConfig, EditMode};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(super) enum FeatureKind {
+    #[default]
+    Ramp,
+    Stairs,
+}
+
+#[derive(Resource, Debug, Default)]
+pub(super) struct FeatureTool {
+    kind: FeatureKind,
+}
+
+/// Left click places a ramp or stairs on the clicked cell, leading towards the edge closest to the
+/// cursor. Ctrl+click removes the feature again.
+pub(super) fn edit_features(
+    edit_config: Res<EditConfig>,
+    feature_tool: Res<FeatureTool>,
+    terrain_raycast: Res<TerrainRaycast>,
+    mut height_grid_q: Query<&mut HeightGrid>,
+    actions: Res<ActionState>,
+    mut history: ResMut<EditHistory>,
+) {
+    if edit_config.mode != EditMode::Feature
+        || !actions.any_just_pressed([Action::EditRaise, Action::EditLower])
+    {
+        return;
+    }
+    let (Some(ray), Some(hit_point)) = (terrain_raycast.ray, terrain_raycast.hit_point) else {
+        return;
+    };
+    let Ok(mut height_grid) = height_grid_q.get_mut(hit_point.entity) else {
+        return;
+    };
+    let coord = hit_point.cell;
+    // Nudge the hit into the terrain so that hitting a cliff picks the edge of the cell behind it.
+    let position = (hit_point.position + *ray.direction * 0.01).xy();
+
+    let feature = actions.just_pressed(Action::EditRaise).then(|| {
+        let direction = closest_edge(position - coord.as_vec2());
+        match feature_tool.kind {
+            FeatureKind::Ramp => CellFeature::Ramp(direction),
+            FeatureKind::Stairs => CellFeature::Stairs(direction),
+        }
+    });
+    history.record(hit_point.entity, &height_grid);
+    height_grid.set_feature(coord, feature);
+}
+
+/// The edge of a cell closest to `local`, a position inside the cell relative to its bottom left.
+fn closest_edge(local: Vec2) -> Direction {
+    let distance = |direction: &Direction| match direction {
+        Direction::Top => 1.0 - local.y,
+        Direction::Right => 1.0 - local.x,
+        Direction::Bottom => local.y,
+        Direction::Left => local.x,
+    };
+    DIRECTIONS
+        .into_iter()
+        .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+        .expect("there are four directions")
+}
+
+pub(super) fn feature_ui(
+    mut contexts: EguiContexts,
+    edit_config: Res<EditConfig>,
+    mut feature_tool: ResMut<FeatureTool>,
+) {
+    use bevy_egui::egui;
+
+    if edit_config.mode != EditMode::Feature {
+        return;
+    }
+
+    egui::Window::new("Features").show(contexts.ctx_mut(), |ui| {
+        ui.radio_value(&mut feature_tool.kind, FeatureKind::Ramp, "Ramp");
+        ui.radio_value(&mut feature_tool.kind, FeatureKind::Stairs, "Stairs");
+    });
+}
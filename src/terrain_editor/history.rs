@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+use crate::{
+    height_grid::HeightGrid,
+    input::actions::{Action, ActionState},
+};
+
+/// Most edits that can be undone, older ones are dropped.
+const MAX_SNAPSHOTS: usize = 50;
+
+/// The height grids as they were before each edit, restored by [`Action::Undo`].
+#[derive(Resource, Debug, Default)]
+pub(super) struct EditHistory {
+    snapshots: Vec<(Entity, HeightGrid)>,
+}
+
+impl EditHistory {
+    /// Remembers `height_grid` before it is edited.
+    pub(super) fn record(&mut self, entity: Entity, height_grid: &HeightGrid) {
+        if self.snapshots.len() == MAX_SNAPSHOTS {
+            self.snapshots.remove(0);
+        }
+        self.snapshots.push((entity, height_grid.clone()));
+    }
+}
+
+pub(super) fn undo(
+    mut contexts: EguiContexts,
+    actions: Res<ActionState>,
+    mut history: ResMut<EditHistory>,
+    mut height_grid_q: Query<&mut HeightGrid>,
+) {
+    // Text fields undo their own edits.
+    if !actions.just_pressed(Action::Undo) || contexts.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+    // Grids that are gone, e.g. after loading a level, cannot be restored.
+    while let Some((entity, snapshot)) = history.snapshots.pop() {
+        if let Ok(mut height_grid) = height_grid_q.get_mut(entity) {
+            height_grid.replace(snapshot);
+            return;
+        }
+    }
+}
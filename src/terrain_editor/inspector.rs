@@ -0,0 +1,100 @@
+use std::any::Any;
+
+use bevy::prelude::*;
+use bevy_inspector_egui::{
+    egui, inspector_egui_impls::InspectorPrimitive, reflect_inspector::InspectorUi,
+};
+
+use crate::height_grid::{corner::Corner, HeightGrid};
+
+/// Shows a [`HeightGrid`] as a cell picker with the four corner heights of the picked cell, and
+/// whether the grid is welded.
+impl InspectorPrimitive for HeightGrid {
+    fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        _options: &dyn Any,
+        id: egui::Id,
+        _env: InspectorUi<'_, '_>,
+    ) -> bool {
+        let mut changed = ui.checkbox(&mut self.welded, "Welded").changed();
+        if self.welded {
+            let seams = self.validate().len();
+            if seams > 0 {
+                ui.colored_label(egui::Color32::YELLOW, format!("{seams} seams"));
+            }
+        }
+
+        if self.cells_count.cmpeq(UVec2::ZERO).any() {
+            ui.label("No cells");
+            return changed;
+        }
+        let selected = pick_cell(ui, id, self.cells_count);
+
+        let cell = *self.get_cell(selected);
+        egui::Grid::new(id.with("corners")).show(ui, |ui| {
+            for row in [
+                [Corner::TopLeft, Corner::TopRight],
+                [Corner::BottomLeft, Corner::BottomRight],
+            ] {
+                for corner in row {
+                    let mut height = cell.get_height(corner);
+                    if ui.add(egui::DragValue::new(&mut height)).changed() {
+                        self.set_height(selected, corner, height);
+                        changed = true;
+                    }
+                }
+                ui.end_row();
+            }
+        });
+
+        let mut void = cell.is_void();
+        if ui.checkbox(&mut void, "Void").changed() {
+            self.set_void(selected, void);
+            changed = true;
+        }
+
+        changed
+    }
+
+    fn ui_readonly(
+        &self,
+        ui: &mut egui::Ui,
+        options: &dyn Any,
+        id: egui::Id,
+        env: InspectorUi<'_, '_>,
+    ) {
+        let mut copy = self.clone();
+        ui.add_enabled_ui(false, |ui| copy.ui(ui, options, id, env));
+    }
+}
+
+/// A button per cell laid out like the grid, the picked cell is remembered across frames.
+fn pick_cell(ui: &mut egui::Ui, id: egui::Id, cells_count: UVec2) -> UVec2 {
+    let mut selected = ui
+        .data(|data| data.get_temp::<UVec2>(id))
+        .unwrap_or_default()
+        .min(cells_count.saturating_sub(UVec2::ONE));
+
+    ui.label(format!("{} x {} cells", cells_count.x, cells_count.y));
+    egui::ScrollArea::both()
+        .id_source(id.with("cells"))
+        .max_height(200.0)
+        .show(ui, |ui| {
+            egui::Grid::new(id.with("cells")).show(ui, |ui| {
+                for y in (0..cells_count.y).rev() {
+                    for x in 0..cells_count.x {
+                        let coord = UVec2::new(x, y);
+                        let label = format!("{x},{y}");
+                        if ui.selectable_label(selected == coord, label).clicked() {
+                            selected = coord;
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+
+    ui.data_mut(|data| data.insert_temp(id, selected));
+    selected
+}
@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+use crate::level::{LoadLevel, SaveLevel};
+
+#[derive(Resource, Debug)]
+pub(super) struct LevelFile {
+    path: String,
+}
+
+impl Default for LevelFile {
+    fn default() -> Self {
+        Self {
+            path: "assets/levels/level.scn.ron".into(),
+        }
+    }
+}
+
+pub(super) fn level_ui(
+    mut contexts: EguiContexts,
+    mut level_file: ResMut<LevelFile>,
+    mut save_events: EventWriter<SaveLevel>,
+    mut load_events: EventWriter<LoadLevel>,
+) {
+    use bevy_egui::egui;
+
+    egui::Window::new("Level").show(contexts.ctx_mut(), |ui| {
+        ui.text_edit_singleline(&mut level_file.path);
+        ui.horizontal(|ui| {
+            if ui.button("Save").clicked() {
+                save_events.send(SaveLevel(PathBuf::from(&level_file.path)));
+            }
+            if ui.button("Load").clicked() {
+                load_events.send(LoadLevel(PathBuf::from(&level_file.path)));
+            }
+        });
+    });
+}
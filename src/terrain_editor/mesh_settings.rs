@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+use crate::height_grid::{
+    mesh_builder::{MeshSettings, SkirtMaterial},
+    HeightGrid,
+};
+
+/// Edits the [`MeshSettings`] of all grids at once, starting from those of the first grid.
+pub(super) fn mesh_settings_ui(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    settings_q: Query<(Entity, Option<&MeshSettings>), With<HeightGrid>>,
+) {
+    use bevy_egui::egui;
+
+    let mut settings = settings_q
+        .iter()
+        .find_map(|(_, settings)| settings.copied())
+        .unwrap_or_default();
+    let before = settings;
+
+    egui::Window::new("Mesh Settings").show(contexts.ctx_mut(), |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Void Floor");
+            ui.add(egui::DragValue::new(&mut settings.void_floor).speed(0.1));
+        });
+
+        let mut skirt = settings.skirt.is_some();
+        ui.checkbox(&mut skirt, "Border Skirt");
+        settings.skirt = skirt.then(|| settings.skirt.unwrap_or_default());
+
+        if let Some(skirt) = settings.skirt.as_mut() {
+            ui.horizontal(|ui| {
+                ui.label("Base Height");
+                ui.add(egui::DragValue::new(&mut skirt.base_height).speed(0.1));
+            });
+            ui.checkbox(&mut skirt.bottom_cap, "Bottom Cap");
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut skirt.material, SkirtMaterial::Base, "Base");
+                ui.radio_value(&mut skirt.material, SkirtMaterial::Cliffs, "Cliffs");
+            });
+        }
+    });
+
+    if settings != before {
+        for (entity, _) in settings_q.iter() {
+            commands.entity(entity).insert(settings);
+        }
+    }
+}
@@ -1,16 +1,49 @@
-use bevy::prelude::*;
+mod bindings;
+mod camera_settings;
+mod diagonal;
+mod feature;
+mod history;
+mod inspector;
+mod level;
+mod mesh_settings;
+mod path_preview;
+mod terrace;
+mod viewshed_preview;
+mod void;
+mod water;
+
+use bevy::{
+    color::palettes::css::{ORANGE, WHITE},
+    prelude::*,
+};
 use bevy_egui::EguiContexts;
+use bevy_inspector_egui::inspector_egui_impls::InspectorEguiImpl;
 
 use crate::{
     height_grid::{
         cell_iter::CellRect,
-        corner::{Corner, CORNERS},
-        flip::{FlipAxis, FlipCorner},
-        mesh_builder::RequiresMeshing,
+        coord::Coord,
+        corner::CORNERS,
+        pathfinding::{edge_step, TraversalConfig},
+        region::CellRegion,
+        terrace::TerraceConfig,
+        theme::Surface,
+        vertex::Vertex,
+        walkability::WalkabilityOverlay,
         HeightGrid,
     },
-    input::{HitPoint, TerrainRaycast},
+    input::{
+        actions::{update_action_state, Action, ActionState},
+        HitPoint, TerrainRaycast,
+    },
 };
+use bindings::BindingCapture;
+use feature::FeatureTool;
+use history::EditHistory;
+use level::LevelFile;
+use path_preview::PathPreview;
+use viewshed_preview::ViewshedPreview;
+use water::WaterTool;
 
 pub struct TerrainEditorPlugin;
 
@@ -21,25 +54,107 @@ impl Plugin for TerrainEditorPlugin {
             range: 0,
             ..default()
         })
-        .add_systems(Update, (edit, config_ui));
+        .register_type::<EditConfig>()
+        .register_type::<EditMode>()
+        .register_type::<BrushShape>()
+        .register_type_data::<HeightGrid, InspectorEguiImpl>()
+        .init_resource::<TerraceConfig>()
+        .init_resource::<PathPreview>()
+        .init_resource::<ViewshedPreview>()
+        .init_resource::<WaterTool>()
+        .init_resource::<FeatureTool>()
+        .init_resource::<LevelFile>()
+        .init_resource::<EditHistory>()
+        .init_resource::<BindingCapture>()
+        .add_systems(
+            Update,
+            (
+                edit,
+                draw_hover_highlight,
+                config_ui,
+                terrace::terrace_ui,
+                path_preview::pick_path_endpoints,
+                path_preview::update_path_preview.after(path_preview::pick_path_endpoints),
+                path_preview::draw_path_preview.after(path_preview::update_path_preview),
+                viewshed_preview::draw_viewshed_preview,
+                water::edit_water,
+                water::water_ui,
+                void::edit_void,
+                diagonal::flip_diagonal,
+                feature::edit_features,
+                feature::feature_ui,
+                mesh_settings::mesh_settings_ui,
+                camera_settings::camera_settings_ui,
+                level::level_ui,
+                history::undo,
+                bindings::bindings_ui,
+            ),
+        )
+        .add_systems(
+            PreUpdate,
+            bindings::capture_binding.after(update_action_state),
+        );
     }
 }
 
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Default, PartialEq, Eq, Reflect)]
 enum EditMode {
     #[default]
     Corner,
     Vertex,
     Cell,
+    Path,
+    Water,
+    Void,
+    FlipDiagonal,
+    Feature,
+}
+
+/// The area the [`EditMode::Vertex`] and [`EditMode::Cell`] modes edit around the cursor.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+enum BrushShape {
+    #[default]
+    Square,
+    Circle,
+    /// The outermost cells of [`BrushShape::Circle`].
+    Ring,
+}
+
+impl BrushShape {
+    /// The cells of the brush around `center` below `limit`, e.g. the size of the grid.
+    fn region(self, center: Coord, range: u32, limit: UVec2) -> CellRegion {
+        match self {
+            BrushShape::Square => CellRect::from_center(center, UVec2::splat(range))
+                .clip(limit)
+                .into(),
+            BrushShape::Circle => CellRegion::circle(center, range, limit),
+            BrushShape::Ring => CellRegion::ring(center, range.saturating_sub(1), range, limit),
+        }
+    }
 }
 
-#[derive(Resource, Debug, Default)]
+/// Largest range the brush can be set to, the hover highlight draws every cell of the brush each
+/// frame.
+const MAX_BRUSH_RANGE: u32 = 32;
+
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
 struct EditConfig {
     strength: i32,
-    range: i32,
+    range: u32,
+    brush: BrushShape,
+    /// Limits [`EditMode::Cell`] to the cells reachable from the clicked one without crossing a
+    /// cliff.
+    same_level: bool,
     mode: EditMode,
 }
-fn config_ui(mut contexts: EguiContexts, mut edit_config: ResMut<EditConfig>) {
+fn config_ui(
+    mut contexts: EguiContexts,
+    mut edit_config: ResMut<EditConfig>,
+    mut traversal_config: ResMut<TraversalConfig>,
+    mut walkability_overlay: ResMut<WalkabilityOverlay>,
+    mut viewshed_preview: ResMut<ViewshedPreview>,
+) {
     use bevy_egui::egui;
 
     egui::Window::new("Editor Config").show(contexts.ctx_mut(), |ui| {
@@ -50,106 +165,381 @@ fn config_ui(mut contexts: EguiContexts, mut edit_config: ResMut<EditConfig>) {
 
         ui.horizontal(|ui| {
             ui.label("Range");
-            ui.add(egui::DragValue::new(&mut edit_config.range).speed(1.0));
+            ui.add(
+                egui::DragValue::new(&mut edit_config.range)
+                    .speed(1.0)
+                    .range(0..=MAX_BRUSH_RANGE),
+            );
         });
+        if matches!(edit_config.mode, EditMode::Vertex | EditMode::Cell) {
+            ui.horizontal(|ui| {
+                ui.label("Brush");
+                ui.radio_value(&mut edit_config.brush, BrushShape::Square, "Square");
+                ui.radio_value(&mut edit_config.brush, BrushShape::Circle, "Circle");
+                ui.radio_value(&mut edit_config.brush, BrushShape::Ring, "Ring");
+            });
+        }
+        if edit_config.mode == EditMode::Cell {
+            ui.checkbox(&mut edit_config.same_level, "Same Level");
+        }
         ui.label("Mode");
 
         ui.radio_value(&mut edit_config.mode, EditMode::Corner, "Corner");
         ui.radio_value(&mut edit_config.mode, EditMode::Vertex, "Vertex");
         ui.radio_value(&mut edit_config.mode, EditMode::Cell, "Cell");
+        ui.radio_value(&mut edit_config.mode, EditMode::Path, "Path");
+        ui.radio_value(&mut edit_config.mode, EditMode::Water, "Water");
+        ui.radio_value(&mut edit_config.mode, EditMode::Void, "Void");
+        ui.radio_value(
+            &mut edit_config.mode,
+            EditMode::FlipDiagonal,
+            "Flip Diagonal",
+        );
+        ui.radio_value(&mut edit_config.mode, EditMode::Feature, "Feature");
+
+        ui.separator();
+        ui.checkbox(&mut walkability_overlay.enabled, "Walkability Overlay");
+
+        if edit_config.mode == EditMode::Path || walkability_overlay.enabled {
+            // Only flag the config as changed on actual edits, every change rebuilds the
+            // walkability maps of all grids.
+            let config = traversal_config.bypass_change_detection();
+            let mut changed = false;
+            ui.horizontal(|ui| {
+                ui.label("Max Step Height");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut config.max_step_height).speed(1.0))
+                    .changed();
+            });
+            ui.horizontal(|ui| {
+                ui.label("Max Slope");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut config.max_slope).speed(0.1))
+                    .changed();
+            });
+            if changed {
+                traversal_config.set_changed();
+            }
+        }
+
+        ui.checkbox(&mut viewshed_preview.enabled, "Viewshed Preview");
+        if viewshed_preview.enabled {
+            ui.horizontal(|ui| {
+                ui.label("Radius");
+                ui.add(egui::DragValue::new(&mut viewshed_preview.radius).speed(1.0));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Eye Height");
+                ui.add(egui::DragValue::new(&mut viewshed_preview.eye_height).speed(0.1));
+            });
+        }
     });
 }
 
 fn edit(
-    mut commands: Commands,
     edit_config: Res<EditConfig>,
     hit_point: Res<TerrainRaycast>,
     mut height_grid_q: Query<&mut HeightGrid>,
-    mouse_button: Res<ButtonInput<MouseButton>>,
+    actions: Res<ActionState>,
+    mut history: ResMut<EditHistory>,
+    mut last_edit: Local<Option<(Entity, Coord, Vertex)>>,
 ) {
-    if !mouse_button.any_just_pressed([MouseButton::Left, MouseButton::Right]) {
+    if matches!(
+        edit_config.mode,
+        EditMode::Path
+            | EditMode::Water
+            | EditMode::Void
+            | EditMode::FlipDiagonal
+            | EditMode::Feature
+    ) || !actions.any_just_pressed([Action::EditRaise, Action::EditLower])
+    {
         return;
     }
 
-    let inverse = mouse_button.just_pressed(MouseButton::Right);
+    let inverse = actions.just_pressed(Action::EditLower);
 
     if let Some(HitPoint {
-        position, entity, ..
+        entity,
+        cell: coord,
+        vertex,
+        ..
     }) = hit_point.hit_point
     {
-        let vec2 = position.xy();
-        let vec2r = vec2.round();
-
-        let Vec2 { x: rx, y: ry } = vec2r;
-        let UVec2 { x, y } = vec2r.as_uvec2();
-
-        let (coord, corner) = match vec2 {
-            Vec2 { x: fx, y: fy } if fx < rx && fy < ry => {
-                (UVec2::new(x - 1, y - 1), Corner::TopRight)
-            }
-            Vec2 { x: fx, .. } if fx < rx => (UVec2::new(x - 1, y), Corner::BottomRight),
-            Vec2 { y: fy, .. } if fy < ry => (UVec2::new(x, y - 1), Corner::TopLeft),
-            _ => (UVec2::new(x, y), Corner::BottomLeft),
-        };
-
         let mut height_grid = height_grid_q
             .get_mut(entity)
             .expect("hit non existing terrain");
 
-        modify_terrain(&mut height_grid, coord, corner, &edit_config, inverse);
+        // Holding the stroke action edits along the line from the previous edit, like a stroke.
+        let stroke_from = last_edit
+            .filter(|_| actions.pressed(Action::EditStroke))
+            .filter(|(last_entity, ..)| *last_entity == entity)
+            .map(|(_, coord, vertex)| (coord, vertex));
+        *last_edit = Some((entity, coord, vertex));
 
-        commands.entity(entity).insert(RequiresMeshing);
+        history.record(entity, &height_grid);
+        modify_terrain(
+            &mut height_grid,
+            coord,
+            vertex,
+            stroke_from,
+            &edit_config,
+            inverse,
+        );
+    }
+}
+
+/// Highlights what the next click edits: the corner or vertex under the cursor, the cells of
+/// the brush or the hovered cell. Hits on cliffs are highlighted in another color.
+fn draw_hover_highlight(
+    mut gizmos: Gizmos,
+    edit_config: Res<EditConfig>,
+    terrain_raycast: Res<TerrainRaycast>,
+    height_grid_q: Query<(&HeightGrid, &GlobalTransform)>,
+) {
+    let Some(hit_point) = terrain_raycast.hit_point else {
+        return;
+    };
+    let Ok((height_grid, transform)) = height_grid_q.get(hit_point.entity) else {
+        return;
+    };
+    let color = match hit_point.surface {
+        Surface::Ground => WHITE,
+        Surface::Cliff => ORANGE,
+    };
+    let mut marker = |position: Vec3| {
+        gizmos.sphere(
+            transform.transform_point(position),
+            Quat::IDENTITY,
+            0.1,
+            color,
+        );
+    };
+
+    match edit_config.mode {
+        EditMode::Corner => {
+            marker(height_grid.get_position(hit_point.cell, hit_point.corner));
+        }
+        EditMode::Vertex => {
+            let center = Coord::from(hit_point.vertex.as_uvec2());
+            let limit = height_grid.cells_count + UVec2::ONE;
+            let brush = edit_config.brush.region(center, edit_config.range, limit);
+            for vertex in brush.iter() {
+                if let Some(position) = height_grid.vertex_position(UVec2::from(vertex)) {
+                    marker(position);
+                }
+            }
+        }
+        EditMode::Cell => {
+            let brush = edit_config.brush.region(
+                hit_point.cell,
+                edit_config.range,
+                height_grid.cells_count,
+            );
+            for cell in brush.iter() {
+                outline_cell(&mut gizmos, height_grid, transform, cell, color);
+            }
+        }
+        EditMode::Path
+        | EditMode::Water
+        | EditMode::Void
+        | EditMode::FlipDiagonal
+        | EditMode::Feature => {
+            outline_cell(&mut gizmos, height_grid, transform, hit_point.cell, color);
+        }
     }
 }
 
+/// Draws the border of `cell` slightly above its corners.
+fn outline_cell(
+    gizmos: &mut Gizmos,
+    height_grid: &HeightGrid,
+    transform: &GlobalTransform,
+    cell: Coord,
+    color: impl Into<Color>,
+) {
+    let corners = CORNERS.map(|corner| {
+        transform.transform_point(height_grid.get_position(cell, corner) + Vec3::Z * 0.02)
+    });
+    gizmos.linestrip(corners.into_iter().chain([corners[0]]), color);
+}
+
 fn modify_terrain(
     height_grid: &mut HeightGrid,
-    coord: UVec2,
-    corner: Corner,
+    coord: Coord,
+    vertex: Vertex,
+    stroke_from: Option<(Coord, Vertex)>,
     EditConfig {
         strength,
         range,
+        brush,
+        same_level,
         mode,
     }: &EditConfig,
     inverse: bool,
 ) {
     let delta = if inverse { -strength } else { *strength };
 
-    let range = match mode {
-        EditMode::Corner => 0,
-        _ => *range as u32,
+    let stroke = |from: Option<Coord>, to: Coord, limit: UVec2| match from {
+        Some(from) => CellRegion::line(from, to)
+            .iter()
+            .fold(CellRegion::default(), |region, center| {
+                region.union(&brush.region(center, *range, limit))
+            }),
+        None => brush.region(to, *range, limit),
     };
-    let cells = CellRect::from_center(coord, UVec2::splat(range));
-    for coord in cells.into_iter() {
-        match mode {
-            EditMode::Corner => modify_corner(height_grid, Some((coord, corner)), delta),
-            EditMode::Vertex => {
-                modify_corner(height_grid, Some((coord, corner)), delta);
-                modify_corner(
-                    height_grid,
-                    (coord, corner).flip(FlipAxis::Horizontal),
-                    delta,
-                );
-                modify_corner(height_grid, (coord, corner).flip(FlipAxis::Vertical), delta);
-                modify_corner(height_grid, (coord, corner).flip(FlipAxis::Diagonal), delta);
+    match mode {
+        EditMode::Corner => {
+            let Some(corner) = vertex.corner_of(coord) else {
+                return;
+            };
+            if height_grid.valid_coord(coord) {
+                let height = height_grid.get_cell(coord).get_height(corner);
+                height_grid.set_height(coord, corner, height.saturating_add_signed(delta));
             }
-
-            EditMode::Cell => {
+        }
+        EditMode::Vertex => {
+            let center = Coord::from(vertex.as_uvec2());
+            let from = stroke_from.map(|(_, from)| Coord::from(from.as_uvec2()));
+            let limit = height_grid.cells_count + UVec2::ONE;
+            for vertex in stroke(from, center, limit).spiral(center) {
+                height_grid.raise_vertex(UVec2::from(vertex), delta);
+            }
+        }
+        EditMode::Cell => {
+            let from = stroke_from.map(|(from, _)| from);
+            let mut region = stroke(from, coord, height_grid.cells_count);
+            if *same_level {
+                region = region.intersection(&level_around(height_grid, coord, &region));
+            }
+            height_grid.modify_region(region, |_, cell| {
                 for corner in CORNERS {
-                    modify_corner(height_grid, Some((coord, corner)), delta);
+                    cell.set_height(corner, cell.get_height(corner).saturating_add_signed(delta));
                 }
-            }
+            })
         }
+        EditMode::Path
+        | EditMode::Water
+        | EditMode::Void
+        | EditMode::FlipDiagonal
+        | EditMode::Feature => {}
     }
 }
 
-fn modify_corner(height_grid: &mut HeightGrid, cell_corner: Option<(UVec2, Corner)>, delta: i32) {
-    if let Some((coord, corner)) = cell_corner {
-        if !height_grid.valid_coord(coord) {
-            return;
+/// The cells connected to `coord` by edges without a cliff or a void cell, within the bounds of
+/// `region`.
+fn level_around(height_grid: &HeightGrid, coord: Coord, region: &CellRegion) -> CellRegion {
+    let Some(bounds) = region.bounds() else {
+        return CellRegion::default();
+    };
+    CellRegion::flood(coord, |cell, direction| {
+        height_grid
+            .neighbour(cell, direction)
+            .is_some_and(|neighbour| {
+                bounds.contains(neighbour) && !height_grid.get_cell(neighbour).is_void()
+            })
+            && edge_step(height_grid, cell, direction) == Some(0)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(heights: &[u32], width: u32) -> HeightGrid {
+        let cells: Vec<_> = heights.iter().map(|&h| (h, h, h, h).into()).collect();
+        HeightGrid::new((width, heights.len() as u32 / width), cells)
+    }
+
+    fn config(brush: BrushShape, range: u32, same_level: bool) -> EditConfig {
+        EditConfig {
+            strength: 1,
+            range,
+            brush,
+            same_level,
+            mode: EditMode::Cell,
         }
-        let cell = height_grid.get_cell_mut(coord);
-        let new_height = cell.get_height(corner).saturating_add_signed(delta);
-        cell.set_height(corner, new_height);
+    }
+
+    /// Raises the cells around `coord` and returns the ones that changed.
+    fn raise(
+        mut height_grid: HeightGrid,
+        coord: Coord,
+        stroke_from: Option<Coord>,
+        config: &EditConfig,
+    ) -> CellRegion {
+        let before = height_grid.clone();
+        let stroke_from = stroke_from.map(|from| (from, Vertex::default()));
+        modify_terrain(
+            &mut height_grid,
+            coord,
+            Vertex::default(),
+            stroke_from,
+            config,
+            false,
+        );
+        before
+            .bounds()
+            .into_iter()
+            .filter(|&cell| before.get_cell(cell) != height_grid.get_cell(cell))
+            .collect()
+    }
+
+    #[test]
+    fn brush_shapes_pick_the_cells() {
+        let flat = grid(&[0; 25], 5);
+        let center = Coord::new(2, 2);
+
+        assert_eq!(
+            raise(
+                flat.clone(),
+                center,
+                None,
+                &config(BrushShape::Circle, 1, false)
+            ),
+            CellRegion::circle(center, 1, UVec2::MAX)
+        );
+        assert_eq!(
+            raise(flat, center, None, &config(BrushShape::Ring, 2, false)),
+            CellRegion::ring(center, 1, 2, UVec2::MAX)
+        );
+    }
+
+    #[test]
+    fn brushes_are_clipped_to_the_grid() {
+        let flat = grid(&[0; 9], 3);
+
+        for brush in [BrushShape::Square, BrushShape::Circle] {
+            assert_eq!(
+                raise(
+                    flat.clone(),
+                    Coord::new(1, 1),
+                    None,
+                    &config(brush, u32::MAX, false)
+                ),
+                flat.bounds().into()
+            );
+        }
+    }
+
+    #[test]
+    fn stroke_edits_along_the_line_from_the_previous_edit() {
+        let flat = grid(&[0; 15], 5);
+        let config = config(BrushShape::Square, 0, false);
+
+        assert_eq!(
+            raise(flat, Coord::new(4, 2), Some(Coord::new(0, 0)), &config),
+            CellRegion::line((0, 0), (4, 2))
+        );
+    }
+
+    #[test]
+    fn same_level_stops_at_cliffs() {
+        let row = grid(&[0, 0, 3, 0, 0], 5);
+        let config = config(BrushShape::Square, 4, true);
+
+        assert_eq!(
+            raise(row, Coord::new(0, 0), None, &config),
+            [Coord::new(0, 0), Coord::new(1, 0)].into_iter().collect()
+        );
     }
 }
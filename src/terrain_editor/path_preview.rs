@@ -0,0 +1,101 @@
+use bevy::{color::palettes::css::ORANGE, prelude::*};
+
+use crate::{
+    height_grid::{
+        coord::Coord,
+        events::HeightGridChanged,
+        pathfinding::{cell_center, find_path, smooth_path, TraversalConfig},
+        HeightGrid,
+    },
+    input::{
+        actions::{Action, ActionState},
+        HitPoint, TerrainRaycast,
+    },
+};
+
+use super::{EditConfig, EditMode};
+
+#[derive(Resource, Debug, Default)]
+pub(super) struct PathPreview {
+    grid: Option<Entity>,
+    start: Option<Coord>,
+    goal: Option<Coord>,
+    markers: Vec<Vec3>,
+    points: Vec<Vec3>,
+}
+
+pub(super) fn pick_path_endpoints(
+    edit_config: Res<EditConfig>,
+    hit_point: Res<TerrainRaycast>,
+    actions: Res<ActionState>,
+    mut preview: ResMut<PathPreview>,
+) {
+    if edit_config.mode != EditMode::Path
+        || !actions.any_just_pressed([Action::EditRaise, Action::EditLower])
+    {
+        return;
+    }
+
+    let Some(HitPoint { entity, cell, .. }) = hit_point.hit_point else {
+        return;
+    };
+
+    if preview.grid != Some(entity) {
+        *preview = PathPreview {
+            grid: Some(entity),
+            ..default()
+        };
+    }
+
+    if actions.just_pressed(Action::EditRaise) {
+        preview.start = Some(cell);
+    } else {
+        preview.goal = Some(cell);
+    }
+}
+
+pub(super) fn update_path_preview(
+    config: Res<TraversalConfig>,
+    mut changed: EventReader<HeightGridChanged>,
+    height_grid_q: Query<&HeightGrid>,
+    mut preview: ResMut<PathPreview>,
+) {
+    let Some(entity) = preview.grid else {
+        return;
+    };
+    let Ok(height_grid) = height_grid_q.get(entity) else {
+        return;
+    };
+    let grid_changed = changed.read().any(|changed| changed.entity == entity);
+    if !preview.is_changed() && !grid_changed && !config.is_changed() {
+        return;
+    }
+
+    preview.markers = [preview.start, preview.goal]
+        .into_iter()
+        .flatten()
+        .map(|cell| cell_center(height_grid, cell))
+        .collect();
+    preview.points = match (preview.start, preview.goal) {
+        (Some(start), Some(goal)) => find_path(height_grid, start, goal, &config)
+            .map(|path| smooth_path(height_grid, &path, &config))
+            .unwrap_or_default(),
+        _ => vec![],
+    };
+}
+
+pub(super) fn draw_path_preview(
+    mut gizmos: Gizmos,
+    edit_config: Res<EditConfig>,
+    preview: Res<PathPreview>,
+) {
+    if edit_config.mode != EditMode::Path {
+        return;
+    }
+
+    let lift = Vec3::Z * 0.05;
+    gizmos.linestrip(preview.points.iter().map(|&point| point + lift), ORANGE);
+    for &marker in preview.markers.iter() {
+        gizmos.circle(marker + lift, Dir3::Z, 0.3, ORANGE);
+    }
+}
@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+use crate::height_grid::{
+    corner::CORNERS,
+    terrace::{terrace, TerraceConfig},
+    HeightGrid,
+};
+
+use super::history::EditHistory;
+
+pub(super) fn terrace_ui(
+    mut contexts: EguiContexts,
+    mut config: ResMut<TerraceConfig>,
+    mut height_grid_q: Query<(Entity, &mut HeightGrid)>,
+    mut history: ResMut<EditHistory>,
+) {
+    use bevy_egui::egui;
+
+    egui::Window::new("Terrace Generator").show(contexts.ctx_mut(), |ui| {
+        ui.label("Terrace Heights");
+        let mut removed = None;
+        for (index, height) in config.terrace_heights.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(height).speed(1.0));
+                if ui.button("Remove").clicked() {
+                    removed = Some(index);
+                }
+            });
+        }
+        if let Some(index) = removed {
+            config.terrace_heights.remove(index);
+        }
+        if ui.button("Add").clicked() {
+            let next = config
+                .terrace_heights
+                .iter()
+                .max()
+                .map_or(0, |height| height + 1);
+            config.terrace_heights.push(next);
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Max Slope Step");
+            ui.add(egui::DragValue::new(&mut config.max_slope_step).speed(1.0));
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Ramp Density");
+            ui.add(egui::Slider::new(&mut config.ramp_density, 0.0..=1.0));
+        });
+
+        if ui.button("Terrace").clicked() {
+            for (entity, mut height_grid) in height_grid_q.iter_mut() {
+                let terraced = terrace(height_grid.cells_count, &config, |position| {
+                    let cell = height_grid.get_cell(position.as_uvec2());
+                    CORNERS
+                        .iter()
+                        .map(|&corner| cell.get_height(corner) as f32)
+                        .sum::<f32>()
+                        / CORNERS.len() as f32
+                });
+                history.record(entity, &height_grid);
+                height_grid.replace(terraced);
+            }
+        }
+    });
+}
@@ -0,0 +1,49 @@
+use bevy::{color::palettes::css::AQUA, prelude::*};
+
+use crate::{
+    height_grid::{line_of_sight::viewshed, pathfinding::cell_center, HeightGrid},
+    input::{HitPoint, TerrainRaycast},
+};
+
+#[derive(Resource, Debug)]
+pub(super) struct ViewshedPreview {
+    pub(super) enabled: bool,
+    pub(super) radius: u32,
+    pub(super) eye_height: f32,
+}
+
+impl Default for ViewshedPreview {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            radius: 5,
+            eye_height: 1.0,
+        }
+    }
+}
+
+pub(super) fn draw_viewshed_preview(
+    mut gizmos: Gizmos,
+    preview: Res<ViewshedPreview>,
+    hit_point: Res<TerrainRaycast>,
+    height_grid_q: Query<(&HeightGrid, &GlobalTransform)>,
+) {
+    if !preview.enabled {
+        return;
+    }
+    let Some(HitPoint {
+        entity, cell: eye, ..
+    }) = hit_point.hit_point
+    else {
+        return;
+    };
+    let Ok((height_grid, transform)) = height_grid_q.get(entity) else {
+        return;
+    };
+
+    let rotation = transform.compute_transform().rotation;
+    for cell in viewshed(height_grid, eye, preview.radius, preview.eye_height, 0.0) {
+        let center = cell.to_world(cell_center(height_grid, cell).z + 0.05, transform);
+        gizmos.rect(center, rotation, Vec2::splat(0.4), AQUA);
+    }
+}
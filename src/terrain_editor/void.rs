@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+
+use crate::{
+    height_grid::{coord::Coord, mesh_builder::MeshSettings, HeightGrid},
+    input::{
+        actions::{Action, ActionState},
+        TerrainRaycast,
+    },
+};
+
+use super::{history::EditHistory, EditConfig, EditMode};
+
+/// Left click turns the clicked cell into a void, Ctrl+click fills the void cell under the
+/// cursor again. Void cells have no ground to hit, so they are picked on the void floor instead.
+pub(super) fn edit_void(
+    edit_config: Res<EditConfig>,
+    terrain_raycast: Res<TerrainRaycast>,
+    mut height_grid_q: Query<(
+        Entity,
+        &mut HeightGrid,
+        Option<&MeshSettings>,
+        &GlobalTransform,
+    )>,
+    actions: Res<ActionState>,
+    mut history: ResMut<EditHistory>,
+) {
+    if edit_config.mode != EditMode::Void
+        || !actions.any_just_pressed([Action::EditRaise, Action::EditLower])
+    {
+        return;
+    }
+    let Some(ray) = terrain_raycast.ray else {
+        return;
+    };
+
+    if actions.just_pressed(Action::EditRaise) {
+        let Some(hit_point) = terrain_raycast.hit_point else {
+            return;
+        };
+        let Ok((entity, mut height_grid, ..)) = height_grid_q.get_mut(hit_point.entity) else {
+            return;
+        };
+        history.record(entity, &height_grid);
+        height_grid.set_void(hit_point.cell, true);
+        return;
+    }
+
+    let closest = height_grid_q
+        .iter()
+        .filter_map(|(entity, height_grid, settings, transform)| {
+            let floor = settings.copied().unwrap_or_default().void_floor;
+            let to_local = transform.affine().inverse();
+            let origin = to_local.transform_point3(ray.origin);
+            let direction = Dir3::new(to_local.transform_vector3(*ray.direction)).ok()?;
+            let local_ray = Ray3d { origin, direction };
+            let distance =
+                local_ray.intersect_plane(Vec3::Z * floor, InfinitePlane3d::new(Vec3::Z))?;
+            let cell = Coord::containing(local_ray.get_point(distance).xy())?;
+            height_grid
+                .try_get_cell(cell)
+                .is_some_and(|cell| cell.is_void())
+                .then_some((entity, cell, distance))
+        })
+        .min_by(|(.., a), (.., b)| a.total_cmp(b));
+
+    if let Some((entity, cell, _)) = closest {
+        let (_, mut height_grid, ..) = height_grid_q.get_mut(entity).expect("grid was queried");
+        history.record(entity, &height_grid);
+        height_grid.set_void(cell, false);
+    }
+}
@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+use crate::{
+    height_grid::{
+        water::{flood_fill, WaterLevel, WaterRegion},
+        HeightGrid,
+    },
+    input::{
+        actions::{Action, ActionState},
+        HitPoint, TerrainRaycast,
+    },
+};
+
+use super::{EditConfig, EditMode};
+
+#[derive(Resource, Debug)]
+pub(super) struct WaterTool {
+    region_height: f32,
+    global_height: f32,
+}
+
+impl Default for WaterTool {
+    fn default() -> Self {
+        Self {
+            region_height: 0.5,
+            global_height: 0.5,
+        }
+    }
+}
+
+/// Left click floods the area around the clicked cell, Ctrl+click drains every region covering
+/// it.
+pub(super) fn edit_water(
+    edit_config: Res<EditConfig>,
+    water_tool: Res<WaterTool>,
+    hit_point: Res<TerrainRaycast>,
+    mut height_grid_q: Query<(&HeightGrid, &mut WaterLevel)>,
+    actions: Res<ActionState>,
+) {
+    if edit_config.mode != EditMode::Water
+        || !actions.any_just_pressed([Action::EditRaise, Action::EditLower])
+    {
+        return;
+    }
+
+    let Some(HitPoint { entity, cell, .. }) = hit_point.hit_point else {
+        return;
+    };
+    let Ok((height_grid, mut water_level)) = height_grid_q.get_mut(entity) else {
+        return;
+    };
+
+    if actions.just_pressed(Action::EditRaise) {
+        water_level.regions.push(WaterRegion {
+            seed: cell,
+            height: water_tool.region_height,
+        });
+    } else {
+        water_level
+            .regions
+            .retain(|region| !flood_fill(height_grid, region.seed, region.height).contains(&cell));
+    }
+}
+
+pub(super) fn water_ui(
+    mut contexts: EguiContexts,
+    edit_config: Res<EditConfig>,
+    mut water_tool: ResMut<WaterTool>,
+    mut water_level_q: Query<&mut WaterLevel>,
+) {
+    use bevy_egui::egui;
+
+    if edit_config.mode != EditMode::Water {
+        return;
+    }
+
+    egui::Window::new("Water").show(contexts.ctx_mut(), |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Region Height");
+            ui.add(egui::DragValue::new(&mut water_tool.region_height).speed(0.1));
+        });
+
+        let mut global = water_level_q
+            .iter()
+            .find_map(|water_level| water_level.global)
+            .is_some();
+        let mut changed = ui.checkbox(&mut global, "Global Water").changed();
+        ui.horizontal(|ui| {
+            ui.label("Global Height");
+            changed |= ui
+                .add(egui::DragValue::new(&mut water_tool.global_height).speed(0.1))
+                .changed();
+        });
+
+        if changed {
+            let height = global.then_some(water_tool.global_height);
+            for mut water_level in water_level_q.iter_mut() {
+                water_level.global = height;
+            }
+        }
+
+        if ui.button("Drain All").clicked() {
+            for mut water_level in water_level_q.iter_mut() {
+                *water_level = WaterLevel::default();
+            }
+        }
+    });
+}